@@ -1,51 +1,65 @@
 /* automatically generated by rust-bindgen 0.59.1 */
 
 #[doc = " Cubism version identifier."]
-pub type csmVersion = ::std::os::raw::c_uint;
+pub type csmVersion = ::core::ffi::c_uint;
 #[doc = " Necessary alignment for mocs (in bytes)."]
-pub const csmAlignofMoc: ::std::os::raw::c_uint = 64;
+pub const csmAlignofMoc: ::core::ffi::c_uint = 64;
 #[doc = " Necessary alignment for models (in bytes)."]
-pub const csmAlignofModel: ::std::os::raw::c_uint = 16;
+pub const csmAlignofModel: ::core::ffi::c_uint = 16;
 #[doc = " Alignment constraints."]
-pub type _bindgen_ty_1 = ::std::os::raw::c_uint;
+pub type _bindgen_ty_1 = ::core::ffi::c_uint;
 #[doc = " Additive blend mode mask."]
-pub const csmBlendAdditive: ::std::os::raw::c_uint = 1;
+pub const csmBlendAdditive: ::core::ffi::c_uint = 1;
 #[doc = " Multiplicative blend mode mask."]
-pub const csmBlendMultiplicative: ::std::os::raw::c_uint = 2;
+pub const csmBlendMultiplicative: ::core::ffi::c_uint = 2;
 #[doc = " Double-sidedness mask."]
-pub const csmIsDoubleSided: ::std::os::raw::c_uint = 4;
+pub const csmIsDoubleSided: ::core::ffi::c_uint = 4;
 #[doc = " Clipping mask inversion mode mask."]
-pub const csmIsInvertedMask: ::std::os::raw::c_uint = 8;
+pub const csmIsInvertedMask: ::core::ffi::c_uint = 8;
 #[doc = " Bit masks for non-dynamic drawable flags."]
-pub type _bindgen_ty_2 = ::std::os::raw::c_uint;
+pub type _bindgen_ty_2 = ::core::ffi::c_uint;
 #[doc = " Flag set when visible."]
-pub const csmIsVisible: ::std::os::raw::c_uint = 1;
+pub const csmIsVisible: ::core::ffi::c_uint = 1;
 #[doc = " Flag set when visibility did change."]
-pub const csmVisibilityDidChange: ::std::os::raw::c_uint = 2;
+pub const csmVisibilityDidChange: ::core::ffi::c_uint = 2;
 #[doc = " Flag set when opacity did change."]
-pub const csmOpacityDidChange: ::std::os::raw::c_uint = 4;
+pub const csmOpacityDidChange: ::core::ffi::c_uint = 4;
 #[doc = " Flag set when draw order did change."]
-pub const csmDrawOrderDidChange: ::std::os::raw::c_uint = 8;
+pub const csmDrawOrderDidChange: ::core::ffi::c_uint = 8;
 #[doc = " Flag set when render order did change."]
-pub const csmRenderOrderDidChange: ::std::os::raw::c_uint = 16;
+pub const csmRenderOrderDidChange: ::core::ffi::c_uint = 16;
 #[doc = " Flag set when vertex positions did change."]
-pub const csmVertexPositionsDidChange: ::std::os::raw::c_uint = 32;
+pub const csmVertexPositionsDidChange: ::core::ffi::c_uint = 32;
+#[doc = " Flag set when blend color did change."]
+pub const csmBlendColorDidChange: ::core::ffi::c_uint = 64;
 #[doc = " Bit masks for dynamic drawable flags."]
-pub type _bindgen_ty_3 = ::std::os::raw::c_uint;
+pub type _bindgen_ty_3 = ::core::ffi::c_uint;
 #[doc = " Bitfield."]
-pub type csmFlags = ::std::os::raw::c_uchar;
+pub type csmFlags = ::core::ffi::c_uchar;
 #[doc = " unknown"]
-pub const csmMocVersion_Unknown: ::std::os::raw::c_uint = 0;
+pub const csmMocVersion_Unknown: ::core::ffi::c_uint = 0;
 #[doc = " moc3 file version 3.0.00 - 3.2.07"]
-pub const csmMocVersion_30: ::std::os::raw::c_uint = 1;
+pub const csmMocVersion_30: ::core::ffi::c_uint = 1;
 #[doc = " moc3 file version 3.3.00 - 3.3.03"]
-pub const csmMocVersion_33: ::std::os::raw::c_uint = 2;
-#[doc = " moc3 file version 4.0.00 -"]
-pub const csmMocVersion_40: ::std::os::raw::c_uint = 3;
+pub const csmMocVersion_33: ::core::ffi::c_uint = 2;
+#[doc = " moc3 file version 4.0.00 - 4.1.05"]
+pub const csmMocVersion_40: ::core::ffi::c_uint = 3;
+#[doc = " moc3 file version 4.2.00 - 4.2.04"]
+pub const csmMocVersion_42: ::core::ffi::c_uint = 4;
+#[doc = " moc3 file version 5.0.00 -"]
+pub const csmMocVersion_50: ::core::ffi::c_uint = 5;
 #[doc = " moc3 file format version."]
-pub type _bindgen_ty_4 = ::std::os::raw::c_uint;
+pub type _bindgen_ty_4 = ::core::ffi::c_uint;
 #[doc = " moc3 version identifier."]
-pub type csmMocVersion = ::std::os::raw::c_uint;
+pub type csmMocVersion = ::core::ffi::c_uint;
+#[doc = " Normal parameter."]
+pub const csmParameterType_Normal: ::core::ffi::c_uint = 0;
+#[doc = " Parameter for blend shape."]
+pub const csmParameterType_BlendShape: ::core::ffi::c_uint = 1;
+#[doc = " Parameter types."]
+pub type _bindgen_ty_5 = ::core::ffi::c_uint;
+#[doc = " Type of parameter."]
+pub type csmParameterType = ::core::ffi::c_int;
 #[doc = " 2 component vector."]
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -58,17 +72,17 @@ pub struct csmVector2 {
 #[test]
 fn bindgen_test_layout_csmVector2() {
     assert_eq!(
-        ::std::mem::size_of::<csmVector2>(),
+        ::core::mem::size_of::<csmVector2>(),
         8usize,
         concat!("Size of: ", stringify!(csmVector2))
     );
     assert_eq!(
-        ::std::mem::align_of::<csmVector2>(),
+        ::core::mem::align_of::<csmVector2>(),
         4usize,
         concat!("Alignment of ", stringify!(csmVector2))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<csmVector2>())).X as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<csmVector2>())).X as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -78,7 +92,7 @@ fn bindgen_test_layout_csmVector2() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<csmVector2>())).Y as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<csmVector2>())).Y as *const _ as usize },
         4usize,
         concat!(
             "Offset of field: ",
@@ -88,11 +102,77 @@ fn bindgen_test_layout_csmVector2() {
         )
     );
 }
+#[doc = " 4 component vector."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct csmVector4 {
+    #[doc = " 1st component."]
+    pub X: f32,
+    #[doc = " 2nd component."]
+    pub Y: f32,
+    #[doc = " 3rd component."]
+    pub Z: f32,
+    #[doc = " 4th component."]
+    pub W: f32,
+}
+#[test]
+fn bindgen_test_layout_csmVector4() {
+    assert_eq!(
+        ::core::mem::size_of::<csmVector4>(),
+        16usize,
+        concat!("Size of: ", stringify!(csmVector4))
+    );
+    assert_eq!(
+        ::core::mem::align_of::<csmVector4>(),
+        4usize,
+        concat!("Alignment of ", stringify!(csmVector4))
+    );
+    assert_eq!(
+        unsafe { &(*(::core::ptr::null::<csmVector4>())).X as *const _ as usize },
+        0usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(csmVector4),
+            "::",
+            stringify!(X)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::core::ptr::null::<csmVector4>())).Y as *const _ as usize },
+        4usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(csmVector4),
+            "::",
+            stringify!(Y)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::core::ptr::null::<csmVector4>())).Z as *const _ as usize },
+        8usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(csmVector4),
+            "::",
+            stringify!(Z)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::core::ptr::null::<csmVector4>())).W as *const _ as usize },
+        12usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(csmVector4),
+            "::",
+            stringify!(W)
+        )
+    );
+}
 #[doc = " Log handler."]
 #[doc = ""]
 #[doc = " @param  message  Null-terminated string message to log."]
 pub type csmLogFunction =
-    ::std::option::Option<unsafe extern "C" fn(message: *const ::std::os::raw::c_char)>;
+    ::core::option::Option<unsafe extern "C" fn(message: *const ::core::ffi::c_char)>;
 extern "C" {
     #[doc = " Queries Core version."]
     #[doc = ""]
@@ -113,10 +193,22 @@ extern "C" {
     #[doc = ""]
     #[doc = " @return csmMocVersion"]
     pub fn csmGetMocVersion(
-        address: *const ::std::os::raw::c_void,
-        size: ::std::os::raw::c_uint,
+        address: *const ::core::ffi::c_void,
+        size: ::core::ffi::c_uint,
     ) -> csmMocVersion;
 }
+extern "C" {
+    #[doc = " Checks consistency of a moc."]
+    #[doc = ""]
+    #[doc = " @param  address  Address of unrevived moc. The address must be aligned to 'csmAlignofMoc'."]
+    #[doc = " @param  size     Size of moc (in bytes)."]
+    #[doc = ""]
+    #[doc = " @return  '1' if Moc is valid; '0' otherwise."]
+    pub fn csmHasMocConsistency(
+        address: *mut ::core::ffi::c_void,
+        size: ::core::ffi::c_uint,
+    ) -> ::core::ffi::c_int;
+}
 extern "C" {
     #[doc = " Queries log handler."]
     #[doc = ""]
@@ -137,8 +229,8 @@ extern "C" {
     #[doc = ""]
     #[doc = " @return  Valid pointer on success; '0' otherwise."]
     pub fn csmReviveMocInPlace(
-        address: *mut ::std::os::raw::c_void,
-        size: ::std::os::raw::c_uint,
+        address: *mut ::core::ffi::c_void,
+        size: ::core::ffi::c_uint,
     ) -> *mut csmMoc;
 }
 extern "C" {
@@ -147,7 +239,7 @@ extern "C" {
     #[doc = " @param  moc  Moc to query."]
     #[doc = ""]
     #[doc = " @return  Valid size on success; '0' otherwise."]
-    pub fn csmGetSizeofModel(moc: *const csmMoc) -> ::std::os::raw::c_uint;
+    pub fn csmGetSizeofModel(moc: *const csmMoc) -> ::core::ffi::c_uint;
 }
 extern "C" {
     #[doc = " Tries to instantiate a model in place."]
@@ -159,8 +251,8 @@ extern "C" {
     #[doc = " @return  Valid pointer on success; '0' otherwise."]
     pub fn csmInitializeModelInPlace(
         moc: *const csmMoc,
-        address: *mut ::std::os::raw::c_void,
-        size: ::std::os::raw::c_uint,
+        address: *mut ::core::ffi::c_void,
+        size: ::core::ffi::c_uint,
     ) -> *mut csmModel;
 }
 extern "C" {
@@ -190,7 +282,7 @@ extern "C" {
     #[doc = " @param[in]  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid count on success; '-1' otherwise."]
-    pub fn csmGetParameterCount(model: *const csmModel) -> ::std::os::raw::c_int;
+    pub fn csmGetParameterCount(model: *const csmModel) -> ::core::ffi::c_int;
 }
 extern "C" {
     #[doc = " Gets parameter IDs."]
@@ -199,7 +291,15 @@ extern "C" {
     #[doc = " @param  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid pointer on success; '0' otherwise."]
-    pub fn csmGetParameterIds(model: *const csmModel) -> *mut *const ::std::os::raw::c_char;
+    pub fn csmGetParameterIds(model: *const csmModel) -> *mut *const ::core::ffi::c_char;
+}
+extern "C" {
+    #[doc = " Gets parameter types."]
+    #[doc = ""]
+    #[doc = " @param  model  Model to query."]
+    #[doc = ""]
+    #[doc = " @return  Valid pointer on success; '0' otherwise."]
+    pub fn csmGetParameterTypes(model: *const csmModel) -> *const csmParameterType;
 }
 extern "C" {
     #[doc = " Gets minimum parameter values."]
@@ -239,7 +339,7 @@ extern "C" {
     #[doc = " @param  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid pointer on success; '0' otherwise."]
-    pub fn csmGetParameterKeyCounts(model: *const csmModel) -> *const ::std::os::raw::c_int;
+    pub fn csmGetParameterKeyCounts(model: *const csmModel) -> *const ::core::ffi::c_int;
 }
 extern "C" {
     #[doc = " Gets key values of each parameter."]
@@ -255,7 +355,7 @@ extern "C" {
     #[doc = " @param  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid count on success; '-1' otherwise."]
-    pub fn csmGetPartCount(model: *const csmModel) -> ::std::os::raw::c_int;
+    pub fn csmGetPartCount(model: *const csmModel) -> ::core::ffi::c_int;
 }
 extern "C" {
     #[doc = " Gets parts IDs."]
@@ -264,7 +364,7 @@ extern "C" {
     #[doc = " @param  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid pointer on success; '0' otherwise."]
-    pub fn csmGetPartIds(model: *const csmModel) -> *mut *const ::std::os::raw::c_char;
+    pub fn csmGetPartIds(model: *const csmModel) -> *mut *const ::core::ffi::c_char;
 }
 extern "C" {
     #[doc = " Gets read/write part opacities buffer."]
@@ -280,7 +380,7 @@ extern "C" {
     #[doc = " @param   model   Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid pointer on success; '0' otherwise."]
-    pub fn csmGetPartParentPartIndices(model: *const csmModel) -> *const ::std::os::raw::c_int;
+    pub fn csmGetPartParentPartIndices(model: *const csmModel) -> *const ::core::ffi::c_int;
 }
 extern "C" {
     #[doc = " Gets number of drawables."]
@@ -288,7 +388,7 @@ extern "C" {
     #[doc = " @param  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid count on success; '-1' otherwise."]
-    pub fn csmGetDrawableCount(model: *const csmModel) -> ::std::os::raw::c_int;
+    pub fn csmGetDrawableCount(model: *const csmModel) -> ::core::ffi::c_int;
 }
 extern "C" {
     #[doc = " Gets drawable IDs."]
@@ -297,7 +397,7 @@ extern "C" {
     #[doc = " @param  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid pointer on success; '0' otherwise."]
-    pub fn csmGetDrawableIds(model: *const csmModel) -> *mut *const ::std::os::raw::c_char;
+    pub fn csmGetDrawableIds(model: *const csmModel) -> *mut *const ::core::ffi::c_char;
 }
 extern "C" {
     #[doc = " Gets constant drawable flags."]
@@ -321,7 +421,7 @@ extern "C" {
     #[doc = " @param  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid pointer on success; '0' otherwise."]
-    pub fn csmGetDrawableTextureIndices(model: *const csmModel) -> *const ::std::os::raw::c_int;
+    pub fn csmGetDrawableTextureIndices(model: *const csmModel) -> *const ::core::ffi::c_int;
 }
 extern "C" {
     #[doc = " Gets drawable draw orders."]
@@ -329,7 +429,7 @@ extern "C" {
     #[doc = " @param  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid pointer on success; '0' otherwise."]
-    pub fn csmGetDrawableDrawOrders(model: *const csmModel) -> *const ::std::os::raw::c_int;
+    pub fn csmGetDrawableDrawOrders(model: *const csmModel) -> *const ::core::ffi::c_int;
 }
 extern "C" {
     #[doc = " Gets drawable render orders."]
@@ -338,7 +438,7 @@ extern "C" {
     #[doc = " @param  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid pointer on success; '0'otherwise."]
-    pub fn csmGetDrawableRenderOrders(model: *const csmModel) -> *const ::std::os::raw::c_int;
+    pub fn csmGetDrawableRenderOrders(model: *const csmModel) -> *const ::core::ffi::c_int;
 }
 extern "C" {
     #[doc = " Gets drawable opacities."]
@@ -354,7 +454,7 @@ extern "C" {
     #[doc = " @param  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid pointer on success; '0' otherwise."]
-    pub fn csmGetDrawableMaskCounts(model: *const csmModel) -> *const ::std::os::raw::c_int;
+    pub fn csmGetDrawableMaskCounts(model: *const csmModel) -> *const ::core::ffi::c_int;
 }
 extern "C" {
     #[doc = " Gets mask indices of each drawable."]
@@ -362,7 +462,7 @@ extern "C" {
     #[doc = " @param  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid pointer on success; '0' otherwise."]
-    pub fn csmGetDrawableMasks(model: *const csmModel) -> *mut *const ::std::os::raw::c_int;
+    pub fn csmGetDrawableMasks(model: *const csmModel) -> *mut *const ::core::ffi::c_int;
 }
 extern "C" {
     #[doc = " Gets number of vertices of each drawable."]
@@ -370,7 +470,7 @@ extern "C" {
     #[doc = " @param  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid pointer on success; '0' otherwise."]
-    pub fn csmGetDrawableVertexCounts(model: *const csmModel) -> *const ::std::os::raw::c_int;
+    pub fn csmGetDrawableVertexCounts(model: *const csmModel) -> *const ::core::ffi::c_int;
 }
 extern "C" {
     #[doc = " Gets vertex position data of each drawable."]
@@ -394,7 +494,7 @@ extern "C" {
     #[doc = " @param  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid pointer on success; '0' otherwise."]
-    pub fn csmGetDrawableIndexCounts(model: *const csmModel) -> *const ::std::os::raw::c_int;
+    pub fn csmGetDrawableIndexCounts(model: *const csmModel) -> *const ::core::ffi::c_int;
 }
 extern "C" {
     #[doc = " Gets triangle index data for each drawable."]
@@ -402,7 +502,31 @@ extern "C" {
     #[doc = " @param  model  Model to query."]
     #[doc = ""]
     #[doc = " @return  Valid pointer on success; '0' otherwise."]
-    pub fn csmGetDrawableIndices(model: *const csmModel) -> *mut *const ::std::os::raw::c_ushort;
+    pub fn csmGetDrawableIndices(model: *const csmModel) -> *mut *const ::core::ffi::c_ushort;
+}
+extern "C" {
+    #[doc = " Gets multiply color data of each drawable."]
+    #[doc = ""]
+    #[doc = " @param  model  Model to query."]
+    #[doc = ""]
+    #[doc = " @return  Valid pointer on success; '0' otherwise."]
+    pub fn csmGetDrawableMultiplyColors(model: *const csmModel) -> *const csmVector4;
+}
+extern "C" {
+    #[doc = " Gets screen color data of each drawable."]
+    #[doc = ""]
+    #[doc = " @param  model  Model to query."]
+    #[doc = ""]
+    #[doc = " @return  Valid pointer on success; '0' otherwise."]
+    pub fn csmGetDrawableScreenColors(model: *const csmModel) -> *const csmVector4;
+}
+extern "C" {
+    #[doc = " Gets parent part indices of each drawable."]
+    #[doc = ""]
+    #[doc = " @param  model  Model to query."]
+    #[doc = ""]
+    #[doc = " @return  Valid pointer on success; '0' otherwise."]
+    pub fn csmGetDrawableParentPartIndices(model: *const csmModel) -> *const ::core::ffi::c_int;
 }
 extern "C" {
     #[doc = " Resets all dynamic drawable flags."]
@@ -0,0 +1,6 @@
+//! The tests of the platform matrix in the build script of `cubism-core-sys`.
+//!
+//! `cargo test` doesn't run the tests of build scripts, so `build/target.rs` is compiled here.
+
+#[path = "../../build/target.rs"]
+pub mod target;
@@ -0,0 +1,267 @@
+//! The platform matrix of the Cubism Core libs.
+//!
+//! It's tested by the `cubism-core-sys-build-tests` crate,
+//! because `cargo test` doesn't run the tests of build scripts.
+
+use std::{fmt, path::PathBuf};
+
+/// The supported targets, which are listed in the error of unsupported targets.
+const SUPPORTED_TARGETS: &[&str] = &[
+    "x86_64-apple-darwin",
+    "aarch64-apple-ios (static lib, IOS_BUILD=device)",
+    "x86_64-apple-ios (static lib, IOS_BUILD=simulator)",
+    "i686/armv7/aarch64-linux-android(eabi)",
+    "i586/i686/x86_64-pc-windows-* (static lib needs the msvc ABI)",
+    "x86_64-unknown-linux-*",
+    "arm/armv7-unknown-linux-* (experimental Raspberry Pi)",
+    "thumbv7a/aarch64/i686/x86_64-uwp-windows-* (experimental, dynamic lib)",
+];
+
+/// The kind of linking the Cubism Core lib.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Link {
+    Static,
+    Dynamic,
+}
+
+impl Link {
+    /// Returns the kind for `cargo:rustc-link-lib`.
+    pub fn kind(self) -> &'static str {
+        match self {
+            Link::Static => "static",
+            Link::Dynamic => "dylib",
+        }
+    }
+}
+
+/// A target which the Cubism Core lib supports.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SupportedTarget {
+    MacOs,
+    IosDevice,
+    IosSimulator,
+    /// With the Android ABI directory.
+    Android(&'static str),
+    /// With the Windows architecture directory.
+    Windows(&'static str),
+    Linux,
+    RaspberryPi,
+    /// With the UWP architecture directory.
+    Uwp(&'static str),
+}
+
+/// The error of an unsupported target.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnsupportedTarget {
+    pub target: String,
+    pub reason: String,
+}
+
+impl UnsupportedTarget {
+    fn new(target: &str, reason: &str) -> Self {
+        Self {
+            target: target.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for UnsupportedTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "unsupported target `{}`: {}", self.target, self.reason)?;
+        write!(f, "the supported targets are:")?;
+        for target in SUPPORTED_TARGETS {
+            write!(f, "\n    {}", target)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SupportedTarget {
+    /// Maps a target triple to [`SupportedTarget`].
+    ///
+    /// `ios_build` is `device` or `simulator`, which is only used for iOS.
+    pub fn new(target: &str, link: Link, ios_build: &str) -> Result<Self, UnsupportedTarget> {
+        let (arch, vendor, sys, abi) = {
+            let mut split = target.split('-');
+            (
+                split.next().unwrap_or(""),
+                split.next().unwrap_or(""),
+                split.next().unwrap_or(""),
+                split.next().unwrap_or(""),
+            )
+        };
+        let unsupported = |reason: &str| Err(UnsupportedTarget::new(target, reason));
+
+        match (vendor, sys) {
+            ("apple", "darwin") => match arch {
+                "x86_64" => Ok(SupportedTarget::MacOs),
+                _ => unsupported("only support x86_64 for macOS"),
+            },
+            ("apple", "ios") => match (link, ios_build, arch) {
+                (Link::Dynamic, _, _) => unsupported("no dynamic lib support for iOS"),
+                (Link::Static, "device", "aarch64") => Ok(SupportedTarget::IosDevice),
+                (Link::Static, "device", _) => unsupported("only support aarch64 for iOS device"),
+                (Link::Static, "simulator", "x86_64") => Ok(SupportedTarget::IosSimulator),
+                (Link::Static, "simulator", _) => {
+                    unsupported("only support x86_64 for iOS simulator")
+                }
+                _ => unsupported("IOS_BUILD should be `device` or `simulator`"),
+            },
+            ("linux", "android") | ("linux", "androideabi") => match arch {
+                "i686" => Ok(SupportedTarget::Android("x86")),
+                "armv7" => Ok(SupportedTarget::Android("armeabi-v7a")),
+                "aarch64" => Ok(SupportedTarget::Android("arm64-v8a")),
+                _ => unsupported("only support i686, armv7 or aarch64 for Android"),
+            },
+            ("pc", "windows") => {
+                let arch = match arch {
+                    "i586" | "i686" => "x86",
+                    "x86_64" => "x86_64",
+                    _ => return unsupported("only support i586, i686 or x86_64 for Windows"),
+                };
+                if link == Link::Static && abi != "msvc" {
+                    unsupported("need msvc ABI to link Live2D Cubism Core's Windows static lib")
+                } else {
+                    Ok(SupportedTarget::Windows(arch))
+                }
+            }
+            ("unknown", "linux") => match arch {
+                "x86_64" => Ok(SupportedTarget::Linux),
+                "arm" | "armv7" => Ok(SupportedTarget::RaspberryPi),
+                _ => unsupported("unsupported Linux architecture"),
+            },
+            ("uwp", "windows") => {
+                if link == Link::Static {
+                    return unsupported("no static lib support for UWP");
+                }
+                match arch {
+                    "thumbv7a" => Ok(SupportedTarget::Uwp("arm")),
+                    "aarch64" => Ok(SupportedTarget::Uwp("arm64")),
+                    "i686" => Ok(SupportedTarget::Uwp("x86")),
+                    "x86_64" => Ok(SupportedTarget::Uwp("x64")),
+                    _ => unsupported("unsupported UWP architecture"),
+                }
+            }
+            _ => unsupported("unsupported platform"),
+        }
+    }
+
+    /// Returns the path of the lib directory relative to the `Core` directory.
+    ///
+    /// The Visual Studio version directory of Windows static libs isn't included.
+    pub fn lib_subpath(self, link: Link, release: bool) -> PathBuf {
+        let mut path = PathBuf::from(match link {
+            Link::Static => "lib",
+            Link::Dynamic => "dll",
+        });
+        let profile = if release { "Release" } else { "Debug" };
+        match self {
+            SupportedTarget::MacOs => path.push("macos"),
+            SupportedTarget::IosDevice => {
+                path.push("ios");
+                path.push(format!("{}-iphoneos", profile));
+            }
+            SupportedTarget::IosSimulator => {
+                path.push("ios");
+                path.push(format!("{}-iphonesimulator", profile));
+            }
+            SupportedTarget::Android(abi) => {
+                path.push("android");
+                path.push(abi);
+            }
+            SupportedTarget::Windows(arch) => {
+                path.push("windows");
+                path.push(arch);
+            }
+            SupportedTarget::Linux => {
+                path.push("linux");
+                path.push("x86_64");
+            }
+            SupportedTarget::RaspberryPi => {
+                path.push("experimental");
+                path.push("rpi");
+            }
+            SupportedTarget::Uwp(arch) => {
+                path.push("experimental");
+                path.push("uwp");
+                path.push(arch);
+            }
+        }
+
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supported_target() {
+        let cases = [
+            ("x86_64-apple-darwin", Link::Static, "lib/macos"),
+            (
+                "aarch64-apple-ios",
+                Link::Static,
+                "lib/ios/Release-iphoneos",
+            ),
+            (
+                "aarch64-linux-android",
+                Link::Dynamic,
+                "dll/android/arm64-v8a",
+            ),
+            (
+                "armv7-linux-androideabi",
+                Link::Static,
+                "lib/android/armeabi-v7a",
+            ),
+            ("x86_64-pc-windows-msvc", Link::Static, "lib/windows/x86_64"),
+            ("i686-pc-windows-gnu", Link::Dynamic, "dll/windows/x86"),
+            ("x86_64-unknown-linux-gnu", Link::Static, "lib/linux/x86_64"),
+            (
+                "armv7-unknown-linux-gnueabihf",
+                Link::Dynamic,
+                "dll/experimental/rpi",
+            ),
+            (
+                "x86_64-uwp-windows-msvc",
+                Link::Dynamic,
+                "dll/experimental/uwp/x64",
+            ),
+        ];
+        for (target, link, subpath) in cases.iter() {
+            let supported = SupportedTarget::new(target, *link, "device").unwrap();
+            assert_eq!(
+                supported.lib_subpath(*link, true),
+                subpath.split('/').collect::<PathBuf>()
+            );
+        }
+        assert_eq!(
+            SupportedTarget::new("x86_64-apple-ios", Link::Static, "simulator"),
+            Ok(SupportedTarget::IosSimulator)
+        );
+    }
+
+    #[test]
+    fn test_unsupported_target() {
+        let cases = [
+            ("aarch64-apple-darwin", Link::Static, "device"),
+            ("aarch64-apple-ios", Link::Dynamic, "device"),
+            ("aarch64-apple-ios", Link::Static, "simulator"),
+            ("aarch64-apple-ios", Link::Static, "watch"),
+            ("x86_64-pc-windows-gnu", Link::Static, "device"),
+            ("aarch64-unknown-linux-gnu", Link::Static, "device"),
+            ("x86_64-uwp-windows-msvc", Link::Static, "device"),
+            ("wasm32-unknown-unknown", Link::Static, "device"),
+        ];
+        for (target, link, ios_build) in cases.iter() {
+            let err = SupportedTarget::new(target, *link, ios_build).unwrap_err();
+            assert_eq!(err.target, *target);
+            let message = err.to_string();
+            assert!(message.contains(&err.reason));
+            assert!(SUPPORTED_TARGETS.iter().all(|t| message.contains(t)));
+        }
+    }
+}
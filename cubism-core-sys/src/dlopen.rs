@@ -0,0 +1,179 @@
+// Loading the Cubism Core lib at runtime, which is included by `lib.rs` with the `dlopen` feature.
+
+use std::{
+    ffi::OsStr,
+    sync::{Mutex, OnceLock},
+};
+
+/// The Cubism Core lib and its functions, which are never unloaded once loaded.
+static CORE_LIB: OnceLock<(libloading::Library, CoreFunctions)> = OnceLock::new();
+/// Serializes the loading of the Cubism Core lib.
+static LOAD_LOCK: Mutex<()> = Mutex::new(());
+
+/// A handle of the Cubism Core lib loaded at runtime.
+///
+/// With the `dlopen` feature, the functions of this crate call into the lib loaded by
+/// [`CoreLib::load`] instead of a linked lib.
+/// If the lib isn't loaded or a function is missing in it, the function returns zero
+/// (or a null pointer) without calling into the lib, which is the failure value of the Cubism
+/// Core API, so the callers should check their results as the Cubism Core API requires.
+///
+/// The functions added after Cubism Core 4.0 are resolved optionally,
+/// so an older Cubism Core lib can be loaded.
+#[derive(Clone, Copy, Debug)]
+pub struct CoreLib {
+    _private: (),
+}
+
+impl CoreLib {
+    /// Loads the Cubism Core lib from `path` and resolves its functions.
+    ///
+    /// Only the first successful loading takes effect,
+    /// and later calls return the handle of the loaded lib without loading again.
+    ///
+    /// # Safety
+    ///
+    /// The lib at `path` should be a Cubism Core lib, and its initialization routines
+    /// will be run when loading.
+    pub unsafe fn load<P: AsRef<OsStr>>(path: P) -> Result<Self, libloading::Error> {
+        let _guard = LOAD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        if CORE_LIB.get().is_none() {
+            let lib = libloading::Library::new(path)?;
+            let functions = CoreFunctions::load(&lib)?;
+            let _ = CORE_LIB.set((lib, functions));
+        }
+
+        Ok(Self { _private: () })
+    }
+
+    /// Returns the handle of the loaded Cubism Core lib,
+    /// or returns [`None`] if it isn't loaded.
+    #[inline]
+    pub fn get() -> Option<Self> {
+        CORE_LIB.get().map(|_| Self { _private: () })
+    }
+}
+
+#[inline]
+fn core_functions() -> Option<&'static CoreFunctions> {
+    CORE_LIB.get().map(|(_, functions)| functions)
+}
+
+macro_rules! dlopen_functions {
+    (
+        required {
+            $(fn $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)?;)*
+        }
+        optional {
+            $(fn $opt_name:ident($($opt_arg:ident: $opt_ty:ty),*) $(-> $opt_ret:ty)?;)*
+        }
+    ) => {
+        /// The functions resolved from the Cubism Core lib.
+        struct CoreFunctions {
+            $($name: unsafe extern "C" fn($($ty),*) $(-> $ret)?,)*
+            $($opt_name: Option<unsafe extern "C" fn($($opt_ty),*) $(-> $opt_ret)?>,)*
+        }
+
+        impl CoreFunctions {
+            unsafe fn load(lib: &libloading::Library) -> Result<Self, libloading::Error> {
+                Ok(Self {
+                    $($name: *lib.get(concat!(stringify!($name), "\0").as_bytes())?,)*
+                    $($opt_name: lib
+                        .get(concat!(stringify!($opt_name), "\0").as_bytes())
+                        .ok()
+                        .map(|f| *f),)*
+                })
+            }
+        }
+
+        $(
+            #[doc = concat!("Calls `", stringify!($name), "` in the Cubism Core lib loaded by [`CoreLib::load`].")]
+            ///
+            /// It returns zero without calling into the lib if the lib isn't loaded.
+            ///
+            /// # Safety
+            ///
+            /// The same as calling the linked function.
+            #[inline]
+            pub unsafe fn $name($($arg: $ty),*) $(-> $ret)? {
+                match core_functions() {
+                    Some(functions) => (functions.$name)($($arg),*),
+                    // SAFETY: the return types of the Cubism Core API are integers, pointers,
+                    // optional function pointers or `()`, whose zero values are valid.
+                    None => core::mem::zeroed(),
+                }
+            }
+        )*
+
+        $(
+            #[doc = concat!("Calls `", stringify!($opt_name), "` in the Cubism Core lib loaded by [`CoreLib::load`].")]
+            ///
+            /// It returns zero without calling into the lib if the lib isn't loaded
+            /// or the function is missing in an older Cubism Core lib.
+            ///
+            /// # Safety
+            ///
+            /// The same as calling the linked function.
+            #[inline]
+            pub unsafe fn $opt_name($($opt_arg: $opt_ty),*) $(-> $opt_ret)? {
+                match core_functions().and_then(|functions| functions.$opt_name) {
+                    Some(function) => function($($opt_arg),*),
+                    // SAFETY: the return types of the Cubism Core API are integers, pointers,
+                    // optional function pointers or `()`, whose zero values are valid.
+                    None => core::mem::zeroed(),
+                }
+            }
+        )*
+    };
+}
+
+dlopen_functions! {
+    required {
+        fn csmGetVersion() -> csmVersion;
+        fn csmGetLatestMocVersion() -> csmMocVersion;
+        fn csmGetMocVersion(address: *const ::core::ffi::c_void, size: ::core::ffi::c_uint) -> csmMocVersion;
+        fn csmGetLogFunction() -> csmLogFunction;
+        fn csmSetLogFunction(handler: csmLogFunction);
+        fn csmReviveMocInPlace(address: *mut ::core::ffi::c_void, size: ::core::ffi::c_uint) -> *mut csmMoc;
+        fn csmGetSizeofModel(moc: *const csmMoc) -> ::core::ffi::c_uint;
+        fn csmInitializeModelInPlace(moc: *const csmMoc, address: *mut ::core::ffi::c_void, size: ::core::ffi::c_uint) -> *mut csmModel;
+        fn csmUpdateModel(model: *mut csmModel);
+        fn csmReadCanvasInfo(model: *const csmModel, outSizeInPixels: *mut csmVector2, outOriginInPixels: *mut csmVector2, outPixelsPerUnit: *mut f32);
+        fn csmGetParameterCount(model: *const csmModel) -> ::core::ffi::c_int;
+        fn csmGetParameterIds(model: *const csmModel) -> *mut *const ::core::ffi::c_char;
+        fn csmGetParameterMinimumValues(model: *const csmModel) -> *const f32;
+        fn csmGetParameterMaximumValues(model: *const csmModel) -> *const f32;
+        fn csmGetParameterDefaultValues(model: *const csmModel) -> *const f32;
+        fn csmGetParameterValues(model: *mut csmModel) -> *mut f32;
+        fn csmGetParameterKeyCounts(model: *const csmModel) -> *const ::core::ffi::c_int;
+        fn csmGetParameterKeyValues(model: *const csmModel) -> *mut *const f32;
+        fn csmGetPartCount(model: *const csmModel) -> ::core::ffi::c_int;
+        fn csmGetPartIds(model: *const csmModel) -> *mut *const ::core::ffi::c_char;
+        fn csmGetPartOpacities(model: *mut csmModel) -> *mut f32;
+        fn csmGetPartParentPartIndices(model: *const csmModel) -> *const ::core::ffi::c_int;
+        fn csmGetDrawableCount(model: *const csmModel) -> ::core::ffi::c_int;
+        fn csmGetDrawableIds(model: *const csmModel) -> *mut *const ::core::ffi::c_char;
+        fn csmGetDrawableConstantFlags(model: *const csmModel) -> *const csmFlags;
+        fn csmGetDrawableDynamicFlags(model: *const csmModel) -> *const csmFlags;
+        fn csmGetDrawableTextureIndices(model: *const csmModel) -> *const ::core::ffi::c_int;
+        fn csmGetDrawableDrawOrders(model: *const csmModel) -> *const ::core::ffi::c_int;
+        fn csmGetDrawableRenderOrders(model: *const csmModel) -> *const ::core::ffi::c_int;
+        fn csmGetDrawableOpacities(model: *const csmModel) -> *const f32;
+        fn csmGetDrawableMaskCounts(model: *const csmModel) -> *const ::core::ffi::c_int;
+        fn csmGetDrawableMasks(model: *const csmModel) -> *mut *const ::core::ffi::c_int;
+        fn csmGetDrawableVertexCounts(model: *const csmModel) -> *const ::core::ffi::c_int;
+        fn csmGetDrawableVertexPositions(model: *const csmModel) -> *mut *const csmVector2;
+        fn csmGetDrawableVertexUvs(model: *const csmModel) -> *mut *const csmVector2;
+        fn csmGetDrawableIndexCounts(model: *const csmModel) -> *const ::core::ffi::c_int;
+        fn csmGetDrawableIndices(model: *const csmModel) -> *mut *const ::core::ffi::c_ushort;
+        fn csmResetDrawableDynamicFlags(model: *mut csmModel);
+    }
+    // Added after Cubism Core 4.0.
+    optional {
+        fn csmHasMocConsistency(address: *mut ::core::ffi::c_void, size: ::core::ffi::c_uint) -> ::core::ffi::c_int;
+        fn csmGetParameterTypes(model: *const csmModel) -> *const csmParameterType;
+        fn csmGetDrawableMultiplyColors(model: *const csmModel) -> *const csmVector4;
+        fn csmGetDrawableScreenColors(model: *const csmModel) -> *const csmVector4;
+        fn csmGetDrawableParentPartIndices(model: *const csmModel) -> *const ::core::ffi::c_int;
+    }
+}
@@ -1,18 +1,35 @@
 //! This crate provides low level bindings to [Cubism Core native API](https://www.live2d.com/en/download/cubism-sdk/download-native/).
 //!
 //! For a safe wrapper, see the `cubism-core` crate.
+//!
+//! With the `dlopen` feature, Cubism Core isn't linked but loaded at runtime by `CoreLib::load`.
 
+#![no_std]
 #![warn(missing_docs)]
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 #![allow(non_upper_case_globals)]
 #![allow(deref_nullptr)]
 
-#[cfg(not(feature = "doc"))]
-include!(concat!(env!("OUT_DIR"), "/cubism_core.rs"));
+#[cfg(any(feature = "dlopen", test))]
+extern crate std;
+
+#[cfg_attr(feature = "dlopen", allow(dead_code))]
+mod bindings {
+    use super::{csmMoc, csmModel};
+
+    #[cfg(not(feature = "doc"))]
+    include!(concat!(env!("OUT_DIR"), "/cubism_core.rs"));
+
+    #[cfg(feature = "doc")]
+    include!("../bindgen/cubism_core.rs");
+}
 
-#[cfg(feature = "doc")]
-include!("../bindgen/cubism_core.rs");
+pub use bindings::*;
+
+// The functions loaded at runtime shadow the linked functions re-exported from `bindings`.
+#[cfg(feature = "dlopen")]
+include!("dlopen.rs");
 
 /// Cubism moc.
 #[repr(C, align(64))]
@@ -31,11 +48,22 @@ pub struct csmModel {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::mem;
+    use core::mem;
 
     #[test]
     fn test_alignment() {
         assert_eq!(mem::align_of::<csmMoc>(), csmAlignofMoc as _);
         assert_eq!(mem::align_of::<csmModel>(), csmAlignofModel as _);
     }
+
+    #[cfg(feature = "dlopen")]
+    #[test]
+    fn test_core_lib_not_loaded() {
+        assert!(CoreLib::get().is_none());
+        unsafe {
+            assert_eq!(csmGetVersion(), 0);
+            assert!(csmGetLogFunction().is_none());
+            assert!(csmGetDrawableParentPartIndices(core::ptr::null()).is_null());
+        }
+    }
 }
@@ -2,12 +2,17 @@
 
 use crate::{
     impl_iter,
-    model::{Model, Vector2},
-    ConstantFlags, DynamicFlags, ModelData, Result,
+    model::{Model, PartParent, Vector2, Vector4},
+    BlendMode, ConstantFlags, DynamicFlags, ModelData, Result,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
 };
 
 /// A static drawable.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StaticDrawable {
     /// The index of a drawable.
     pub index: usize,
@@ -23,6 +28,8 @@ pub struct StaticDrawable {
     pub vertex_uvs: Vec<Vector2>,
     /// The indices of a drawable.
     pub indices: Vec<usize>,
+    /// The parent part of a drawable.
+    pub parent_part: PartParent,
 }
 
 /// Static drawables.
@@ -85,12 +92,89 @@ impl<'a> ModelData for StaticDrawables<'a> {
                 .iter()
                 .map(|i| *i as usize)
                 .collect(),
+            parent_part: *self.model.drawable_parent_parts().get_unchecked(index),
         }
     }
 }
 
 impl_iter!(StaticDrawables<'a>, StaticDrawable, Vec<StaticDrawable>);
 
+/// A static drawable which borrows its data from [`Model`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StaticDrawableRef<'a> {
+    /// The index of a drawable.
+    pub index: usize,
+    /// The ID of a drawable.
+    pub id: &'a str,
+    /// The constant flags of a drawable.
+    pub constant_flags: ConstantFlags,
+    /// The texture index of a drawable.
+    pub texture_index: usize,
+    /// The masks of a drawable.
+    pub masks: &'a [u32],
+    /// The vertex uvx of a drawable.
+    pub vertex_uvs: &'a [Vector2],
+    /// The indices of a drawable.
+    pub indices: &'a [u16],
+    /// The parent part of a drawable.
+    pub parent_part: PartParent,
+}
+
+/// Static drawables which borrow their data from [`Model`].
+#[derive(Debug)]
+pub struct StaticDrawableRefs<'a> {
+    model: &'a Model<'a>,
+    /// The initialization value is 0.
+    start: usize,
+    /// The initialization value is the count of drawables.
+    end: usize,
+}
+
+impl<'a> StaticDrawableRefs<'a> {
+    #[inline]
+    pub(crate) fn new(model: &'a Model<'a>) -> Self {
+        Self {
+            model,
+            start: 0,
+            end: model.drawable_count(),
+        }
+    }
+}
+
+impl<'a> ModelData for StaticDrawableRefs<'a> {
+    type Data = StaticDrawableRef<'a>;
+
+    #[inline]
+    fn count(&self) -> usize {
+        self.model.drawable_count()
+    }
+
+    #[inline]
+    fn index<T: AsRef<str>>(&self, id: T) -> Option<usize> {
+        self.model.drawable_index(id)
+    }
+
+    #[inline]
+    unsafe fn get_index_unchecked(&self, index: usize) -> Self::Data {
+        StaticDrawableRef {
+            index,
+            id: self.model.drawable_ids().get_unchecked(index),
+            constant_flags: *self.model.drawable_constant_flags().get_unchecked(index),
+            texture_index: *self.model.drawable_texture_indices().get_unchecked(index) as _,
+            masks: self.model.drawable_masks().get_unchecked(index),
+            vertex_uvs: self.model.drawable_vertex_uvs().get_unchecked(index),
+            indices: self.model.drawable_indices().get_unchecked(index),
+            parent_part: *self.model.drawable_parent_parts().get_unchecked(index),
+        }
+    }
+}
+
+impl_iter!(
+    StaticDrawableRefs<'a>,
+    StaticDrawableRef<'a>,
+    Vec<StaticDrawableRef<'a>>
+);
+
 /// A dynamic drawable.
 #[derive(Clone, Debug, PartialEq)]
 pub struct DynamicDrawable {
@@ -108,6 +192,14 @@ pub struct DynamicDrawable {
     pub opacity: f32,
     /// The vertex positions of a drawable.
     pub vertex_positions: Vec<Vector2>,
+    /// The multiply color of a drawable.
+    ///
+    /// [`BLEND_COLOR_DID_CHANGE`](DynamicFlags::BLEND_COLOR_DID_CHANGE) is raised when it has been changed.
+    pub multiply_color: Vector4,
+    /// The screen color of a drawable.
+    ///
+    /// [`BLEND_COLOR_DID_CHANGE`](DynamicFlags::BLEND_COLOR_DID_CHANGE) is raised when it has been changed.
+    pub screen_color: Vector4,
 }
 
 /// Dyanmic Drawables.
@@ -158,6 +250,19 @@ impl<'a> ModelData for DynamicDrawables<'a> {
                 .drawable_vertex_positions()
                 .get_unchecked(index)
                 .to_vec(),
+            multiply_color: *self.model.drawable_multiply_colors().get_unchecked(index),
+            screen_color: *self.model.drawable_screen_colors().get_unchecked(index),
+        })
+    }
+}
+
+impl<'a> DynamicDrawables<'a> {
+    /// Returns an iterator which only yields visible drawables and errors.
+    #[inline]
+    pub fn visible_only(self) -> impl Iterator<Item = Result<DynamicDrawable>> + 'a {
+        self.filter(|d| match d {
+            Ok(d) => d.dynamic_flags.contains(DynamicFlags::IS_VISIBLE),
+            Err(_) => true,
         })
     }
 }
@@ -167,3 +272,219 @@ impl_iter!(
     Result<DynamicDrawable>,
     Result<Vec<DynamicDrawable>>
 );
+
+/// The static and dynamic data of a drawable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawableInfo {
+    /// The index of a drawable.
+    pub index: usize,
+    /// The ID of a drawable.
+    pub id: String,
+    /// The constant flags of a drawable.
+    pub constant_flags: ConstantFlags,
+    /// The texture index of a drawable.
+    pub texture_index: usize,
+    /// The masks of a drawable.
+    pub masks: Vec<usize>,
+    /// The vertex uvx of a drawable.
+    pub vertex_uvs: Vec<Vector2>,
+    /// The indices of a drawable.
+    pub indices: Vec<usize>,
+    /// The parent part of a drawable.
+    pub parent_part: PartParent,
+    /// The draw order of a drawable.
+    pub draw_order: i32,
+    /// The render order of a drawable.
+    pub render_order: i32,
+    /// The opacity of a drawable.
+    pub opacity: f32,
+    /// The vertex positions of a drawable.
+    pub vertex_positions: Vec<Vector2>,
+}
+
+impl DrawableInfo {
+    /// Creates [`DrawableInfo`] from a static drawable and a dynamic drawable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the indices of two drawables aren't equal.
+    #[inline]
+    pub fn new(static_drawable: StaticDrawable, dynamic_drawable: DynamicDrawable) -> Self {
+        assert_eq!(static_drawable.index, dynamic_drawable.index);
+        Self {
+            index: static_drawable.index,
+            id: static_drawable.id,
+            constant_flags: static_drawable.constant_flags,
+            texture_index: static_drawable.texture_index,
+            masks: static_drawable.masks,
+            vertex_uvs: static_drawable.vertex_uvs,
+            indices: static_drawable.indices,
+            parent_part: static_drawable.parent_part,
+            draw_order: dynamic_drawable.draw_order,
+            render_order: dynamic_drawable.render_order,
+            opacity: dynamic_drawable.opacity,
+            vertex_positions: dynamic_drawable.vertex_positions,
+        }
+    }
+}
+
+/// The indices of drawables which have been changed by updating the model.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ChangedDrawables {
+    /// The indices of drawables whose vertex positions have been changed.
+    pub vertex_positions: Vec<usize>,
+    /// The indices of drawables whose opacities have been changed.
+    pub opacities: Vec<usize>,
+    /// The indices of drawables whose render orders have been changed.
+    pub render_orders: Vec<usize>,
+    /// The indices of drawables whose draw orders have been changed.
+    pub draw_orders: Vec<usize>,
+}
+
+impl ChangedDrawables {
+    /// Returns `true` if no drawable has been changed.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vertex_positions.is_empty()
+            && self.opacities.is_empty()
+            && self.render_orders.is_empty()
+            && self.draw_orders.is_empty()
+    }
+}
+
+/// The render settings of a drawable decoded from its constant flags, texture index and masks.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrawableRenderInfo {
+    /// The blend mode of a drawable.
+    pub blend_mode: BlendMode,
+    /// Whether a drawable is double-sided.
+    pub is_double_sided: bool,
+    /// Whether the masks of a drawable are inverted.
+    pub is_inverted_mask: bool,
+    /// The texture index of a drawable.
+    pub texture_index: usize,
+    /// The count of the masks of a drawable.
+    pub mask_count: usize,
+}
+
+/// A vertex of a triangle with its color.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ColoredVertex {
+    /// The position of a vertex.
+    pub position: Vector2,
+    /// The uv of a vertex.
+    pub uv: Vector2,
+    /// The color of a vertex, which is the multiply color of its drawable scaled by the opacity.
+    pub color: Vector4,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::log::{set_logger, DefaultLogger};
+    use crate::read_haru_moc;
+
+    #[test]
+    fn test_dynamic_drawable_colors() -> Result<()> {
+        #[cfg(feature = "std")]
+        set_logger(DefaultLogger);
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        for drawable in model.dynamic_drawables() {
+            let drawable = drawable?;
+            let (r, g, b, a) = drawable.multiply_color.x_y_z_w();
+            assert!(r.is_finite() && g.is_finite() && b.is_finite() && a.is_finite());
+            let (r, g, b, a) = drawable.screen_color.x_y_z_w();
+            assert!(r.is_finite() && g.is_finite() && b.is_finite() && a.is_finite());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drawable_info() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        let info = model.drawable_info(0)?;
+        assert_eq!(info.id, model.static_drawables().get_index(0).id);
+        assert_eq!(info.id, model.dynamic_drawables().get_index(0)?.id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_static_drawable_parent_part() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        for (drawable, parent) in model.static_drawables().zip(model.drawable_parent_parts()) {
+            assert_eq!(drawable.parent_part, *parent);
+            if let Some(p) = parent.parent() {
+                assert!(p < model.part_count());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_static_drawable_refs() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        assert_eq!(model.static_drawable_refs().len(), model.drawable_count());
+        for (r, d) in model.static_drawable_refs().zip(model.static_drawables()) {
+            assert_eq!(r.index, d.index);
+            assert_eq!(r.id, d.id);
+            assert_eq!(r.constant_flags, d.constant_flags);
+            assert_eq!(r.texture_index, d.texture_index);
+            assert_eq!(r.vertex_uvs, &d.vertex_uvs[..]);
+            assert_eq!(r.indices.len(), d.indices.len());
+            assert_eq!(r.parent_part, d.parent_part);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_visible_only() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        let visible = model
+            .dynamic_drawables()
+            .visible_only()
+            .collect::<Result<Vec<_>>>()?;
+        assert!(visible
+            .iter()
+            .all(|d| d.dynamic_flags.contains(DynamicFlags::IS_VISIBLE)));
+        assert_eq!(
+            visible.len(),
+            model
+                .drawable_dynamic_flags()?
+                .iter()
+                .filter(|f| f.contains(DynamicFlags::IS_VISIBLE))
+                .count()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drawable_render_info() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        for d in model.static_drawable_refs() {
+            let info = model.drawable_render_info(d.index);
+            assert_eq!(info.blend_mode, d.constant_flags.blend_mode());
+            assert_eq!(
+                info.is_double_sided,
+                d.constant_flags.contains(ConstantFlags::IS_DOUBLE_SIDED)
+            );
+            assert_eq!(
+                info.is_inverted_mask,
+                d.constant_flags.contains(ConstantFlags::IS_INVERTED_MASK)
+            );
+            assert_eq!(info.texture_index, d.texture_index);
+            assert_eq!(info.mask_count, d.masks.len());
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
+use alloc::{string::String, vec::Vec};
+
 /// `Result` for this crate.
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 /// `Error` for this crate.
 #[derive(Debug)]
@@ -10,34 +12,55 @@ pub enum Error {
     MocDataTooLarge,
     /// Invalid `moc3` data.
     InvalidMocData,
+    /// Inconsistent `moc3` data.
+    InconsistentMocData,
     /// Failed to initialize model.
     InitializeModelError,
     /// Invalid count.
     InvalidCount(&'static str),
     /// Failed to get data.
     GetDataError(&'static str),
-    /// Invalid flags.
-    InvalidFlags(&'static str),
+    /// Invalid flags, with the kind of flags and the invalid bits.
+    InvalidFlags(&'static str, u8),
     /// Failed to read/write file.
+    #[cfg(feature = "std")]
     FileIoError(std::io::Error),
+    /// The length of a slice (the first value) isn't equal to the expected length (the second value).
+    SliceLengthNotEqual(usize, usize),
+    /// IDs which don't exist.
+    UnknownIds(Vec<String>),
+    /// The Cubism Core lib isn't loaded by [`CoreLib::load`](crate::CoreLib::load).
+    #[cfg(feature = "dlopen")]
+    CoreLibNotLoaded,
 }
 
-impl std::fmt::Display for Error {
+impl core::fmt::Display for Error {
     #[inline]
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Error::InvalidMocVersion(v) => write!(f, "unsupported moc version: {}", v),
             Error::MocDataTooLarge => write!(f, "the size of moc3 data is too large"),
             Error::InvalidMocData => write!(f, "invalid moc3 data"),
+            Error::InconsistentMocData => write!(f, "inconsistent moc3 data"),
             Error::InitializeModelError => write!(f, "failed to initialize model"),
             Error::InvalidCount(s) => write!(f, "invalid count of {}", *s),
             Error::GetDataError(s) => write!(f, "failed to get {}", *s),
-            Error::InvalidFlags(s) => write!(f, "invalid {} flags", *s),
+            Error::InvalidFlags(s, bits) => write!(f, "invalid {} flags: {:#010b}", *s, *bits),
+            #[cfg(feature = "std")]
             Error::FileIoError(e) => write!(f, "{}", *e),
+            Error::SliceLengthNotEqual(len, expected) => write!(
+                f,
+                "the length of slice {} isn't equal to {}",
+                *len, *expected
+            ),
+            Error::UnknownIds(ids) => write!(f, "IDs don't exist: {}", ids.join(", ")),
+            #[cfg(feature = "dlopen")]
+            Error::CoreLibNotLoaded => write!(f, "the Cubism Core lib isn't loaded"),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {
     #[inline]
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
@@ -45,18 +68,94 @@ impl std::error::Error for Error {
             Error::InvalidMocVersion(_) => None,
             Error::MocDataTooLarge => None,
             Error::InvalidMocData => None,
+            Error::InconsistentMocData => None,
             Error::InitializeModelError => None,
             Error::InvalidCount(_) => None,
             Error::GetDataError(_) => None,
-            Error::InvalidFlags(_) => None,
+            Error::InvalidFlags(_, _) => None,
             Error::FileIoError(e) => Some(e),
+            Error::SliceLengthNotEqual(_, _) => None,
+            Error::UnknownIds(_) => None,
+            #[cfg(feature = "dlopen")]
+            Error::CoreLibNotLoaded => None,
+        }
+    }
+}
+
+/// [`FileIoError`](Error::FileIoError) is compared by [`kind`](std::io::Error::kind)
+/// because [`std::io::Error`] doesn't implement [`PartialEq`].
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Error::InvalidMocVersion(a), Error::InvalidMocVersion(b)) => a == b,
+            (Error::MocDataTooLarge, Error::MocDataTooLarge) => true,
+            (Error::InvalidMocData, Error::InvalidMocData) => true,
+            (Error::InconsistentMocData, Error::InconsistentMocData) => true,
+            (Error::InitializeModelError, Error::InitializeModelError) => true,
+            (Error::InvalidCount(a), Error::InvalidCount(b)) => a == b,
+            (Error::GetDataError(a), Error::GetDataError(b)) => a == b,
+            (Error::InvalidFlags(a, b), Error::InvalidFlags(c, d)) => a == c && b == d,
+            #[cfg(feature = "std")]
+            (Error::FileIoError(a), Error::FileIoError(b)) => a.kind() == b.kind(),
+            (Error::SliceLengthNotEqual(a, b), Error::SliceLengthNotEqual(c, d)) => {
+                a == c && b == d
+            }
+            (Error::UnknownIds(a), Error::UnknownIds(b)) => a == b,
+            #[cfg(feature = "dlopen")]
+            (Error::CoreLibNotLoaded, Error::CoreLibNotLoaded) => true,
+            _ => false,
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for Error {
     #[inline]
     fn from(err: std::io::Error) -> Self {
         Error::FileIoError(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::ToString, vec};
+    #[cfg(feature = "std")]
+    use std::io::{Error as IoError, ErrorKind};
+
+    #[test]
+    fn test_error_eq() {
+        assert_eq!(Error::GetDataError("a"), Error::GetDataError("a"));
+        assert_ne!(Error::GetDataError("a"), Error::GetDataError("b"));
+        assert_ne!(Error::GetDataError("a"), Error::InvalidCount("a"));
+        assert_eq!(
+            Error::InvalidFlags("dynamic", 0x80).to_string(),
+            "invalid dynamic flags: 0b10000000"
+        );
+        assert_ne!(
+            Error::InvalidFlags("dynamic", 0x80),
+            Error::InvalidFlags("dynamic", 0x40)
+        );
+        assert_eq!(
+            Error::SliceLengthNotEqual(1, 2),
+            Error::SliceLengthNotEqual(1, 2)
+        );
+        assert_eq!(
+            Error::UnknownIds(vec!["a".into(), "b".into()]).to_string(),
+            "IDs don't exist: a, b"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_io_error_eq() {
+        assert_eq!(
+            Error::from(IoError::new(ErrorKind::NotFound, "a")),
+            Error::from(IoError::new(ErrorKind::NotFound, "b"))
+        );
+        assert_ne!(
+            Error::from(IoError::new(ErrorKind::NotFound, "a")),
+            Error::from(IoError::new(ErrorKind::PermissionDenied, "a"))
+        );
+    }
+}
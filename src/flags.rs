@@ -20,6 +20,61 @@ impl ConstantFlags {
     pub(crate) fn is_valid(&self) -> bool {
         (self.bits() & !Self::all().bits()) == 0
     }
+
+    /// Returns the blend mode.
+    ///
+    /// [`BLEND_ADDITIVE`](Self::BLEND_ADDITIVE) and [`BLEND_MULTIPLICATIVE`](Self::BLEND_MULTIPLICATIVE)
+    /// are mutually exclusive in the Cubism spec, and it's [`BlendMode::Normal`] if neither is set.
+    #[inline]
+    pub fn blend_mode(&self) -> BlendMode {
+        debug_assert!(!self.contains(Self::BLEND_ADDITIVE | Self::BLEND_MULTIPLICATIVE));
+        if self.contains(Self::BLEND_ADDITIVE) {
+            BlendMode::Additive
+        } else if self.contains(Self::BLEND_MULTIPLICATIVE) {
+            BlendMode::Multiplicative
+        } else {
+            BlendMode::Normal
+        }
+    }
+}
+
+/// The blend mode of a drawable.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlendMode {
+    /// Normal blend mode.
+    Normal,
+    /// Additive blend mode.
+    Additive,
+    /// Multiplicative blend mode.
+    Multiplicative,
+}
+
+impl Default for BlendMode {
+    #[inline]
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// [`ConstantFlags`] is serialized as its bits.
+#[cfg(feature = "serde")]
+impl serde::Serialize for ConstantFlags {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.bits())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ConstantFlags {
+    #[inline]
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bits = <u8 as serde::Deserialize>::deserialize(deserializer)?;
+        Self::from_bits(bits).ok_or_else(|| {
+            serde::de::Error::custom(alloc::format!("invalid constant flags: {}", bits))
+        })
+    }
 }
 
 bitflags! {
@@ -38,6 +93,10 @@ bitflags! {
         const RENDER_ORDER_DID_CHANGE = cubism_core_sys::csmRenderOrderDidChange as _;
         /// A bit is raised when the vertex positions of a drawable has been changed.
         const VERTEX_POSITIONS_DID_CHANGE = cubism_core_sys::csmVertexPositionsDidChange as _;
+        /// A bit is raised when the multiply color or the screen color of a drawable has been changed.
+        ///
+        /// It's added in Cubism Core 4.2, and it's never raised by an older Core lib.
+        const BLEND_COLOR_DID_CHANGE = cubism_core_sys::csmBlendColorDidChange as _;
     }
 }
 
@@ -47,3 +106,25 @@ impl DynamicFlags {
         (self.bits() & !Self::all().bits()) == 0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blend_mode() {
+        assert_eq!(ConstantFlags::empty().blend_mode(), BlendMode::Normal);
+        assert_eq!(
+            ConstantFlags::IS_DOUBLE_SIDED.blend_mode(),
+            BlendMode::Normal
+        );
+        assert_eq!(
+            (ConstantFlags::BLEND_ADDITIVE | ConstantFlags::IS_INVERTED_MASK).blend_mode(),
+            BlendMode::Additive
+        );
+        assert_eq!(
+            ConstantFlags::BLEND_MULTIPLICATIVE.blend_mode(),
+            BlendMode::Multiplicative
+        );
+    }
+}
@@ -1,7 +1,23 @@
 //! Rust API for [Cubism Core native library](https://www.live2d.com/en/download/cubism-sdk/download-native/).
-
+//!
+//! It supports `no_std` + `alloc` when the default `std` feature is disabled,
+//! but the I/O paths and [`DefaultLogger`](log::DefaultLogger) are only available with `std`.
+//! The float math which isn't in `core` is provided by [`libm`](https://docs.rs/libm) then.
+//!
+//! Linking needs Cubism Core 5.0 or newer. The features which need a newer Cubism Core lib
+//! fall back with an older Core lib, according to
+//! [`MocVersion::latest_version`]: blend colors, drawable parent parts and the moc consistency
+//! check of [`Moc::new`] need Core 4.2, and parameter types need Core 5.0.
+//!
+//! With the `dlopen` feature, Cubism Core is loaded at runtime by `CoreLib::load` instead of
+//! being linked, and [`Moc`] returns an error if it isn't loaded. An older Cubism Core lib
+//! can be loaded because the functions added after Core 4.0 are resolved optionally.
+
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
+extern crate alloc;
+
 pub mod drawable;
 pub mod log;
 pub mod model;
@@ -13,6 +29,9 @@ mod flags;
 mod moc;
 mod version;
 
+pub use aligned_utils::bytes::AlignedBytes;
+#[cfg(feature = "dlopen")]
+pub use cubism_core_sys::CoreLib;
 pub use error::*;
 pub use flags::*;
 pub use moc::*;
@@ -25,6 +44,10 @@ pub(crate) const ALIGN_OF_MOC: usize = cubism_core_sys::csmAlignofMoc as _;
 pub(crate) const ALIGN_OF_MODEL: usize = cubism_core_sys::csmAlignofModel as _;
 
 /// A trait for getting data from [`Model`].
+///
+/// [`Data`](Self::Data) can borrow from the model for allocation-free reads,
+/// like [`StaticParameterRef`](parameter::StaticParameterRef),
+/// [`StaticPartRef`](part::StaticPartRef) and [`StaticDrawableRef`](drawable::StaticDrawableRef).
 pub trait ModelData {
     /// Data type.
     type Data;
@@ -74,7 +97,7 @@ pub trait ModelData {
 
 macro_rules! impl_iter {
     ($iter:ty, $item:ty, $collect:ty) => {
-        impl<'a> std::iter::Iterator for $iter {
+        impl<'a> core::iter::Iterator for $iter {
             type Item = $item;
 
             #[inline]
@@ -98,7 +121,7 @@ macro_rules! impl_iter {
             }
         }
 
-        impl<'a> std::iter::DoubleEndedIterator for $iter {
+        impl<'a> core::iter::DoubleEndedIterator for $iter {
             #[inline]
             fn next_back(&mut self) -> Option<Self::Item> {
                 if self.start < self.end {
@@ -111,8 +134,8 @@ macro_rules! impl_iter {
             }
         }
 
-        impl<'a> std::iter::ExactSizeIterator for $iter {}
-        impl<'a> std::iter::FusedIterator for $iter {}
+        impl<'a> core::iter::ExactSizeIterator for $iter {}
+        impl<'a> core::iter::FusedIterator for $iter {}
 
         impl<'a> $iter {
             /// Gets all data.
@@ -126,8 +149,8 @@ macro_rules! impl_iter {
 
 pub(crate) use impl_iter;
 
-#[cfg(test)]
-pub(crate) fn read_haru_moc() -> Result<moc::Moc> {
+#[cfg(all(test, feature = "std"))]
+pub(crate) fn haru_moc_path() -> std::path::PathBuf {
     use std::env;
     use std::path::PathBuf;
 
@@ -141,5 +164,35 @@ pub(crate) fn read_haru_moc() -> Result<moc::Moc> {
     haru_moc.push("Haru");
     haru_moc.push("Haru.moc3");
 
-    moc::Moc::from_file(haru_moc)
+    haru_moc
+}
+
+#[cfg(all(test, feature = "std"))]
+pub(crate) fn haru_moc_data() -> alloc::vec::Vec<u8> {
+    std::fs::read(haru_moc_path()).expect("failed to read Haru.moc3")
+}
+
+#[cfg(all(test, feature = "std"))]
+pub(crate) fn read_haru_moc() -> Result<moc::Moc> {
+    moc::Moc::from_file(haru_moc_path())
+}
+
+/// Haru's moc embedded at compile time, because there is no file I/O without `std`.
+#[cfg(all(test, not(feature = "std"), not(feature = "doc")))]
+const HARU_MOC: &[u8] = include_bytes!(concat!(
+    env!("LIVE2D_CUBISM"),
+    "/Samples/Resources/Haru/Haru.moc3"
+));
+/// The `doc` feature doesn't link the Cubism Core lib, so the tests are only compiled.
+#[cfg(all(test, not(feature = "std"), feature = "doc"))]
+const HARU_MOC: &[u8] = &[];
+
+#[cfg(all(test, not(feature = "std")))]
+pub(crate) fn haru_moc_data() -> alloc::vec::Vec<u8> {
+    HARU_MOC.to_vec()
+}
+
+#[cfg(all(test, not(feature = "std")))]
+pub(crate) fn read_haru_moc() -> Result<moc::Moc> {
+    moc::Moc::new(HARU_MOC)
 }
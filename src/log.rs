@@ -1,6 +1,9 @@
 //! Logger for the Cubism Core lib.
 
-use std::{borrow::Cow, ffi::CStr, os::raw::c_char};
+use alloc::{borrow::Cow, ffi::CString};
+use core::ffi::{c_char, CStr};
+#[cfg(feature = "log")]
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 /// Log function type.
 pub type LogFunction = unsafe extern "C" fn(message: *const c_char);
@@ -24,9 +27,11 @@ pub trait Logger {
 }
 
 /// Default logger. Just print message to the console.
+#[cfg(feature = "std")]
 #[derive(Clone, Copy, Debug)]
 pub struct DefaultLogger;
 
+#[cfg(feature = "std")]
 impl Logger for DefaultLogger {
     #[inline]
     fn log<'a>(message: impl Into<Cow<'a, str>>) {
@@ -34,7 +39,50 @@ impl Logger for DefaultLogger {
     }
 }
 
+#[cfg(feature = "log")]
+static LOG_CRATE_LEVEL: AtomicUsize = AtomicUsize::new(::log::Level::Info as usize);
+
+/// Logger which forwards messages to the [`log`](::log) crate with the target `cubism`.
+///
+/// The level of messages is [`Info`](::log::Level::Info) by default,
+/// and it can be changed by [`set_level`](Self::set_level) at any time,
+/// even after calling [`set_logger`] with this logger.
+#[cfg(feature = "log")]
+#[derive(Clone, Copy, Debug)]
+pub struct LogCrateLogger;
+
+#[cfg(feature = "log")]
+impl LogCrateLogger {
+    /// Sets the level of messages from the Cubism Core lib.
+    #[inline]
+    pub fn set_level(level: ::log::Level) {
+        LOG_CRATE_LEVEL.store(level as usize, Ordering::Relaxed);
+    }
+
+    /// Returns the level of messages from the Cubism Core lib.
+    #[inline]
+    pub fn level() -> ::log::Level {
+        match LOG_CRATE_LEVEL.load(Ordering::Relaxed) {
+            1 => ::log::Level::Error,
+            2 => ::log::Level::Warn,
+            3 => ::log::Level::Info,
+            4 => ::log::Level::Debug,
+            _ => ::log::Level::Trace,
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+impl Logger for LogCrateLogger {
+    #[inline]
+    fn log<'a>(message: impl Into<Cow<'a, str>>) {
+        ::log::log!(target: "cubism", Self::level(), "{}", message.into());
+    }
+}
+
 /// Set the logger in the Cubism Core lib.
+///
+/// With the `dlopen` feature, it does nothing if the Cubism Core lib isn't loaded.
 #[inline]
 pub fn set_logger<T: Logger>(_: T) {
     unsafe {
@@ -43,19 +91,46 @@ pub fn set_logger<T: Logger>(_: T) {
 }
 
 /// Gets the logger function in the Cubism Core lib.
+///
+/// With the `dlopen` feature, it returns [`None`] if the Cubism Core lib isn't loaded.
 #[inline]
 pub fn get_logger() -> Option<LogFunction> {
     unsafe { cubism_core_sys::csmGetLogFunction() }
 }
 
+/// Logs a message with the logger in the Cubism Core lib if it's set.
+pub(crate) fn log_message(message: &str) {
+    if let (Some(log), Ok(message)) = (get_logger(), CString::new(message)) {
+        // SAFETY: the message is a valid C string.
+        unsafe { log(message.as_ptr()) }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// A logger which is available without `std`.
+    struct TestLogger;
+
+    impl Logger for TestLogger {
+        fn log<'a>(_: impl Into<Cow<'a, str>>) {}
+    }
+
     #[test]
     fn test_logger() {
         assert!(get_logger().is_none());
-        set_logger(DefaultLogger);
+        set_logger(TestLogger);
         assert!(get_logger().is_some());
     }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_log_crate_logger() {
+        assert_eq!(LogCrateLogger::level(), ::log::Level::Info);
+        LogCrateLogger::set_level(::log::Level::Warn);
+        assert_eq!(LogCrateLogger::level(), ::log::Level::Warn);
+        LogCrateLogger::log("test");
+        LogCrateLogger::set_level(::log::Level::Info);
+    }
 }
@@ -1,11 +1,62 @@
-use crate::{Error, MocVersion, Result, ALIGN_OF_MOC};
+use crate::{Error, MocVersion, Model, Result, ALIGN_OF_MOC};
 use aligned_utils::bytes::AlignedBytes;
-use std::{fs::File, io::Read, os::raw::c_uint, path::Path, sync::Arc};
+use alloc::{sync::Arc, vec::Vec};
+use core::ffi::c_uint;
+#[cfg(feature = "std")]
+use std::{
+    fs::File,
+    io::{ErrorKind, Read},
+    path::{Path, PathBuf},
+};
 
 /// Cubism moc.
+///
+/// It's [`Send`] and [`Sync`], because the revived moc buffer is never mutated
+/// after construction, and the Cubism Core lib only reads from `csmMoc`.
 #[derive(Clone, Debug)]
 pub struct Moc {
     moc: Arc<AlignedBytes>,
+    /// The hash of `moc3` data before reviving.
+    content_hash: u64,
+}
+
+/// The info on a `moc3` file from [`Moc::inspect`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MocInfo {
+    /// `moc3` file format version.
+    pub version: MocVersion,
+    /// The size of `moc3` data.
+    pub size: usize,
+    /// Whether `moc3` data is consistent.
+    ///
+    /// It's always `Some(false)` if the version isn't supported by the Cubism Core lib,
+    /// and [`None`] if the Cubism Core lib is older than 4.2, which can't check the consistency.
+    pub is_consistent: Option<bool>,
+}
+
+/// Returns `true` if the Cubism Core lib provides `csmHasMocConsistency`,
+/// which is added in Cubism Core 4.2.
+#[inline]
+fn can_check_consistency() -> bool {
+    MocVersion::latest_version() >= MocVersion::Version42
+}
+
+/// Returns `true` if the version is known and supported by the Cubism Core lib.
+#[inline]
+fn is_supported_version(version: cubism_core_sys::csmMocVersion) -> bool {
+    version != cubism_core_sys::csmMocVersion_Unknown
+        && MocVersion::from(version) <= MocVersion::latest_version()
+}
+
+/// Returns an error if the Cubism Core lib isn't loaded with the `dlopen` feature.
+#[inline]
+fn check_core_lib() -> Result<()> {
+    #[cfg(feature = "dlopen")]
+    if crate::CoreLib::get().is_none() {
+        return Err(Error::CoreLibNotLoaded);
+    }
+
+    Ok(())
 }
 
 #[inline]
@@ -13,42 +64,257 @@ fn get_moc_version(data: &AlignedBytes) -> cubism_core_sys::csmMocVersion {
     unsafe { cubism_core_sys::csmGetMocVersion(data.as_ptr().cast(), data.len() as _) }
 }
 
+/// Reads `moc3` data into an aligned buffer, where `len_hint` is the expected length.
+///
+/// The data is read into the buffer directly if the hint is exact,
+/// otherwise it's read to EOF and copied into a new aligned buffer once.
+#[cfg(feature = "std")]
+fn read_aligned<R: Read>(mut reader: R, len_hint: usize) -> Result<AlignedBytes> {
+    let mut data = AlignedBytes::new_zeroed(len_hint.min(c_uint::MAX as _), ALIGN_OF_MOC);
+    let mut filled = 0;
+    while filled < data.len() {
+        match reader.read(&mut data[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let mut rest = Vec::new();
+    let _ = reader.read_to_end(&mut rest)?;
+    if filled == data.len() && rest.is_empty() {
+        return Ok(data);
+    }
+
+    let len = filled + rest.len();
+    if len > c_uint::MAX as _ {
+        return Err(Error::MocDataTooLarge);
+    }
+    let mut bytes = Vec::with_capacity(len);
+    bytes.extend_from_slice(&data[..filled]);
+    bytes.extend_from_slice(&rest);
+
+    Ok(AlignedBytes::new_from_slice(&bytes, ALIGN_OF_MOC))
+}
+
+/// Returns the length in the metadata of a file as a hint for [`read_aligned`].
+#[cfg(feature = "std")]
+#[inline]
+fn file_len_hint(file: &File) -> Result<usize> {
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    if len > c_uint::MAX as _ {
+        Err(Error::MocDataTooLarge)
+    } else {
+        Ok(len as _)
+    }
+}
+
+/// Hashes data with 64-bit FNV-1a.
+#[inline]
+fn fnv1a_hash(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    data.iter().fold(OFFSET_BASIS, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(PRIME)
+    })
+}
+
 impl Moc {
     /// Creates [`Moc`].
+    ///
+    /// The consistency of `moc3` data is checked before reviving.
+    /// The check needs Cubism Core 4.2 or newer, and only the version of `moc3` data is checked
+    /// with an older Core lib, like [`new_unchecked`](Self::new_unchecked).
+    ///
+    /// With the `dlopen` feature, `Error::CoreLibNotLoaded` is returned if the Cubism Core lib
+    /// isn't loaded. Other types need a [`Moc`] to be created, so they never run without the lib.
+    #[inline]
     pub fn new<T: AsRef<[u8]>>(moc3_data: T) -> Result<Self> {
-        if moc3_data.as_ref().len() > c_uint::MAX as _ {
+        // SAFETY: the consistency is checked.
+        unsafe { Self::new_with_check(moc3_data.as_ref(), true) }
+    }
+
+    /// Creates [`Moc`] with the Cubism Core lib loaded at runtime.
+    ///
+    /// It's the same as [`new`](Self::new), but the handle ensures that
+    /// [`Error::CoreLibNotLoaded`] isn't returned.
+    #[cfg(feature = "dlopen")]
+    #[inline]
+    pub fn new_with_core<T: AsRef<[u8]>>(_core: &crate::CoreLib, moc3_data: T) -> Result<Self> {
+        Self::new(moc3_data)
+    }
+
+    /// Creates [`Moc`] without checking the consistency of `moc3` data.
+    ///
+    /// # Safety
+    ///
+    /// The caller should make sure `moc3` data is consistent,
+    /// otherwise reviving inconsistent data may cause undefined behavior in the Cubism Core lib.
+    #[inline]
+    pub unsafe fn new_unchecked<T: AsRef<[u8]>>(moc3_data: T) -> Result<Self> {
+        Self::new_with_check(moc3_data.as_ref(), false)
+    }
+
+    unsafe fn new_with_check(moc3_data: &[u8], check_consistency: bool) -> Result<Self> {
+        if moc3_data.len() > c_uint::MAX as _ {
             return Err(Error::MocDataTooLarge);
         }
-        let mut data = AlignedBytes::new_from_slice(moc3_data.as_ref(), ALIGN_OF_MOC);
-        debug_assert_eq!(data.len(), moc3_data.as_ref().len());
+        let data = AlignedBytes::new_from_slice(moc3_data, ALIGN_OF_MOC);
+        debug_assert_eq!(data.len(), moc3_data.len());
+
+        Self::from_aligned(data, check_consistency)
+    }
+
+    /// Revives aligned `moc3` data, whose length should be checked before.
+    unsafe fn from_aligned(mut data: AlignedBytes, check_consistency: bool) -> Result<Self> {
+        check_core_lib()?;
         let version = get_moc_version(&data);
+        if !is_supported_version(version) {
+            return Err(Error::InvalidMocVersion(version));
+        } else if check_consistency
+            && can_check_consistency()
+            && cubism_core_sys::csmHasMocConsistency(data.as_mut_ptr().cast(), data.len() as _) == 0
+        {
+            return Err(Error::InconsistentMocData);
+        }
 
-        unsafe {
-            if MocVersion::from(version) > MocVersion::latest_version() {
-                Err(Error::InvalidMocVersion(version))
-            } else if cubism_core_sys::csmReviveMocInPlace(
-                data.as_mut_ptr().cast(),
-                data.len() as _,
-            )
-            .is_null()
-            {
-                Err(Error::InvalidMocData)
-            } else {
-                Ok(Self {
-                    moc: Arc::new(data),
-                })
-            }
+        // hash the data which has been validated, but before reviving modifies it in place.
+        let content_hash = fnv1a_hash(&data);
+        if cubism_core_sys::csmReviveMocInPlace(data.as_mut_ptr().cast(), data.len() as _).is_null()
+        {
+            Err(Error::InvalidMocData)
+        } else {
+            Ok(Self {
+                moc: Arc::new(data),
+                content_hash,
+            })
+        }
+    }
+
+    /// Creates [`Moc`] from a reader which provides `moc3` data.
+    ///
+    /// The data is read into an aligned buffer directly if the size hint of
+    /// [`Read::bytes`] is exact, like a byte slice or a [`BufReader`](std::io::BufReader)
+    /// of it, otherwise it's read to EOF and copied once.
+    /// If the length of `moc3` data is known, [`from_reader_with_len`](Self::from_reader_with_len)
+    /// can avoid copying the data for any reader.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self> {
+        // only the size hint is used, and no byte is read through `bytes()`.
+        #[allow(clippy::unbuffered_bytes)]
+        let (len_hint, _) = reader.by_ref().bytes().size_hint();
+        let data = read_aligned(reader, len_hint)?;
+
+        // SAFETY: the length is checked and the consistency is checked.
+        unsafe { Self::from_aligned(data, true) }
+    }
+
+    /// Creates [`Moc`] from a reader which provides `moc3` data of length `len`.
+    ///
+    /// The data is read into an aligned buffer directly without an intermediate copy.
+    #[cfg(feature = "std")]
+    pub fn from_reader_with_len<R: Read>(mut reader: R, len: usize) -> Result<Self> {
+        if len > c_uint::MAX as _ {
+            return Err(Error::MocDataTooLarge);
         }
+        let mut data = AlignedBytes::new_zeroed(len, ALIGN_OF_MOC);
+        reader.read_exact(&mut data)?;
+
+        // SAFETY: the length is checked and the consistency is checked.
+        unsafe { Self::from_aligned(data, true) }
     }
 
     /// Creates [`Moc`] from `moc3` file.
-    #[inline]
+    ///
+    /// The length in the file metadata is only used as a hint for reading the file
+    /// into an aligned buffer directly, and the file is always read to EOF,
+    /// so files with a zero or stale length (e.g. procfs files or FIFOs) can be read too.
+    #[cfg(feature = "std")]
     pub fn from_file<T: AsRef<Path>>(moc3_file: T) -> Result<Self> {
-        let mut file = File::open(moc3_file)?;
-        let mut data = Vec::new();
-        let _ = file.read_to_end(&mut data)?;
+        let file = File::open(moc3_file)?;
+        let len_hint = file_len_hint(&file)?;
+        let data = read_aligned(file, len_hint)?;
 
-        Self::new(data)
+        // SAFETY: the length is checked and the consistency is checked.
+        unsafe { Self::from_aligned(data, true) }
+    }
+
+    /// Returns `true` if the version of `moc3` data is known and not newer than
+    /// the latest version supported by the Cubism Core lib,
+    /// which can be checked before creating [`Moc`].
+    ///
+    /// The data is copied into an aligned buffer if it isn't aligned to `csmAlignofMoc`.
+    pub fn is_supported_by_runtime(moc3_data: &[u8]) -> bool {
+        if moc3_data.len() > c_uint::MAX as _ {
+            return false;
+        }
+        let version = if moc3_data.as_ptr().align_offset(ALIGN_OF_MOC) == 0 {
+            unsafe {
+                cubism_core_sys::csmGetMocVersion(moc3_data.as_ptr().cast(), moc3_data.len() as _)
+            }
+        } else {
+            get_moc_version(&AlignedBytes::new_from_slice(moc3_data, ALIGN_OF_MOC))
+        };
+
+        is_supported_version(version)
+    }
+
+    /// Inspects `moc3` file without reviving it or creating a model.
+    ///
+    /// It's cheaper than [`from_file`](Self::from_file) for validating many files,
+    /// but the whole file is still read into an aligned buffer
+    /// because checking the consistency needs all `moc3` data.
+    #[cfg(feature = "std")]
+    pub fn inspect<T: AsRef<Path>>(moc3_file: T) -> Result<MocInfo> {
+        check_core_lib()?;
+        let file = File::open(moc3_file)?;
+        let len_hint = file_len_hint(&file)?;
+        let mut data = read_aligned(file, len_hint)?;
+
+        let version = get_moc_version(&data);
+        let is_consistent = if !is_supported_version(version) {
+            Some(false)
+        } else if can_check_consistency() {
+            Some(unsafe {
+                cubism_core_sys::csmHasMocConsistency(data.as_mut_ptr().cast(), data.len() as _)
+                    != 0
+            })
+        } else {
+            None
+        };
+
+        Ok(MocInfo {
+            version: version.into(),
+            size: data.len(),
+            is_consistent,
+        })
+    }
+
+    /// Creates [`Moc`] from a memory-mapped `moc3` file.
+    ///
+    /// The mapped data is copied into an aligned buffer only once.
+    /// The copy can't be skipped even if the map happens to be aligned to `csmAlignofMoc`
+    /// because reviving modifies the data in place, which isn't allowed for a shared file map.
+    ///
+    /// # Safety
+    ///
+    /// The caller should make sure the file isn't modified by other processes
+    /// while it is being mapped and copied.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn from_mmap<T: AsRef<Path>>(moc3_file: T) -> Result<Self> {
+        let file = File::open(moc3_file)?;
+        let map = memmap2::Mmap::map(&file)?;
+
+        Self::new(&map[..])
+    }
+
+    /// Creates [`Model`] from the moc without moving it.
+    ///
+    /// The revived moc data is shared by all models created from the same moc,
+    /// so it's cheap to create many models of one character.
+    #[inline]
+    pub fn instantiate<'a>(&self) -> Result<Model<'a>> {
+        Model::new(self.clone())
     }
 
     /// Returns [`Moc`] format version.
@@ -57,12 +323,47 @@ impl Moc {
         get_moc_version(&self.moc).into()
     }
 
+    /// Returns the raw `moc3` file format version number,
+    /// which is useful when [`version`](Self::version) is unknown.
+    #[inline]
+    pub fn raw_version(&self) -> u32 {
+        get_moc_version(&self.moc)
+    }
+
     /// Returns the size of moc.
     #[inline]
     pub fn moc_size(&self) -> usize {
         self.moc.len()
     }
 
+    /// Returns the bytes of the revived moc.
+    ///
+    /// Reviving modifies `moc3` data in place, so the returning bytes are an in-memory image
+    /// rather than a valid `moc3` file, and they can't be passed to [`Moc::new`] again.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.moc
+    }
+
+    /// Copies the bytes of the revived moc into a vector.
+    ///
+    /// See [`as_bytes`](Self::as_bytes) for the caveat about reviving.
+    #[inline]
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    /// Returns a stable hash of `moc3` data, which can be used as a cache key.
+    ///
+    /// The hash is computed from `moc3` data before reviving
+    /// because reviving modifies the data in place.
+    /// It's computed once when creating [`Moc`] after the data is validated,
+    /// which reads the whole data one more time, and it's cheap to call this method.
+    #[inline]
+    pub fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
     /// Returns a pointer which points to [`csmMoc`](cubism_core_sys::csmMoc).
     ///
     /// The caller should make sure the returning pointer won't live longer than [`Moc`].
@@ -72,7 +373,13 @@ impl Moc {
     }
 }
 
-impl std::convert::TryFrom<&[u8]> for Moc {
+// `Moc` must stay `Send` and `Sync` automatically, see the docs of `Moc`.
+const _: fn() = || {
+    fn assert<T: Send + Sync>() {}
+    assert::<Moc>();
+};
+
+impl core::convert::TryFrom<&[u8]> for Moc {
     type Error = Error;
 
     #[inline]
@@ -81,20 +388,233 @@ impl std::convert::TryFrom<&[u8]> for Moc {
     }
 }
 
+/// The bytes are always copied, because the allocation of [`Vec`] can't be reused
+/// by [`AlignedBytes`] with a different alignment.
+impl core::convert::TryFrom<Vec<u8>> for Moc {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(data: Vec<u8>) -> Result<Self> {
+        Self::new(data)
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::convert::TryFrom<&Path> for Moc {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(path: &Path) -> Result<Self> {
+        Self::from_file(path)
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::convert::TryFrom<PathBuf> for Moc {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(path: PathBuf) -> Result<Self> {
+        Self::from_file(path)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{haru_moc_data, read_haru_moc};
+    #[cfg(feature = "std")]
     use crate::{
+        haru_moc_path,
         log::{set_logger, DefaultLogger},
-        read_haru_moc,
     };
 
     #[test]
     fn test_moc() -> Result<()> {
+        #[cfg(feature = "std")]
         set_logger(DefaultLogger);
         let moc = read_haru_moc()?;
         assert!(moc.version().is_version30());
+        assert_eq!(MocVersion::new(moc.raw_version()), moc.version());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_content_hash() -> Result<()> {
+        let moc1 = read_haru_moc()?;
+        let moc2 = read_haru_moc()?;
+        assert_eq!(moc1.content_hash(), moc2.content_hash());
+        // the test vectors of 64-bit FNV-1a.
+        assert_eq!(fnv1a_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a_hash(b"foobar"), 0x8594_4171_f739_67e8);
 
         Ok(())
     }
+
+    #[test]
+    fn test_inconsistent_moc() -> Result<()> {
+        let data = haru_moc_data();
+        assert_eq!(
+            Moc::new(&data[..data.len() / 2]).unwrap_err(),
+            Error::InconsistentMocData
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_moc_send_sync() -> Result<()> {
+        let moc = read_haru_moc()?;
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let moc = moc.clone();
+                std::thread::spawn(move || -> Result<usize> {
+                    let model = crate::Model::new(moc)?;
+                    Ok(model.drawable_count())
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.join().expect("thread panicked")? > 0);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_as_bytes() -> Result<()> {
+        let moc = read_haru_moc()?;
+        assert_eq!(moc.as_bytes().len(), moc.moc_size());
+        assert_eq!(moc.to_vec(), moc.as_bytes());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_from_reader() -> Result<()> {
+        let data = haru_moc_data();
+        let moc = read_haru_moc()?;
+        assert_eq!(
+            Moc::from_reader(&data[..])?.content_hash(),
+            moc.content_hash()
+        );
+        assert_eq!(
+            Moc::from_reader_with_len(&data[..], data.len())?.content_hash(),
+            moc.content_hash()
+        );
+        assert!(matches!(
+            Moc::from_reader_with_len(&data[..], data.len() + 1),
+            Err(Error::FileIoError(_))
+        ));
+        // a reader without a size hint.
+        assert_eq!(
+            Moc::from_reader(std::io::Cursor::new(&data))?.content_hash(),
+            moc.content_hash()
+        );
+        // exact, short, stale and zero length hints.
+        for len_hint in [data.len(), data.len() / 2, data.len() + 16, 0] {
+            assert_eq!(&read_aligned(&data[..], len_hint)?[..], &data[..]);
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_from_mmap() -> Result<()> {
+        let moc = unsafe { Moc::from_mmap(haru_moc_path())? };
+        assert_eq!(moc.content_hash(), read_haru_moc()?.content_hash());
+        assert_eq!(moc.version(), read_haru_moc()?.version());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_moc_try_from() -> Result<()> {
+        use core::convert::TryInto;
+
+        let data = haru_moc_data();
+        let from_slice: Moc = data.as_slice().try_into()?;
+        let from_vec: Moc = data.try_into()?;
+        assert_eq!(from_vec.as_bytes(), from_slice.as_bytes());
+        #[cfg(feature = "std")]
+        {
+            let from_path: Moc = haru_moc_path().as_path().try_into()?;
+            assert_eq!(from_path.as_bytes(), from_slice.as_bytes());
+            let from_path_buf: Moc = haru_moc_path().try_into()?;
+            assert_eq!(from_path_buf.as_bytes(), from_slice.as_bytes());
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_moc_inspect() -> Result<()> {
+        let info = Moc::inspect(haru_moc_path())?;
+        let moc = read_haru_moc()?;
+        assert_eq!(info.version, moc.version());
+        assert_eq!(info.size, moc.moc_size());
+        assert_eq!(info.is_consistent, Some(true));
+
+        /// Removes the temporary file even if the test fails.
+        struct TempFile(std::path::PathBuf);
+
+        impl Drop for TempFile {
+            fn drop(&mut self) {
+                let _ = std::fs::remove_file(&self.0);
+            }
+        }
+
+        let file = TempFile(std::env::temp_dir().join(format!(
+            "cubism_core_test_moc_inspect_{}.moc3",
+            std::process::id()
+        )));
+        let data = haru_moc_data();
+        std::fs::write(&file.0, &data[..data.len() / 2])?;
+        let info = Moc::inspect(&file.0)?;
+        assert_eq!(info.is_consistent, Some(false));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_supported_by_runtime() -> Result<()> {
+        let data = haru_moc_data();
+        assert!(Moc::is_supported_by_runtime(&data));
+        let aligned = AlignedBytes::new_from_slice(&data, ALIGN_OF_MOC);
+        assert!(Moc::is_supported_by_runtime(&aligned));
+        assert!(!Moc::is_supported_by_runtime(&[0; 64]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_moc_instantiate() -> Result<()> {
+        let moc = read_haru_moc()?;
+        let models = (0..100)
+            .map(|_| moc.instantiate())
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(Arc::strong_count(&moc.moc), 101);
+        assert!(models
+            .iter()
+            .all(|m| m.moc().as_bytes().as_ptr() == moc.as_bytes().as_ptr()));
+        drop(models);
+        assert_eq!(Arc::strong_count(&moc.moc), 1);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "dlopen")]
+    #[test]
+    fn test_core_lib_not_loaded() {
+        assert!(crate::CoreLib::get().is_none());
+        assert_eq!(crate::CubismVersion::version().version, 0);
+        assert_eq!(MocVersion::latest_version(), MocVersion::VersionUnknown(0));
+        assert_eq!(Moc::new([0; 64]).unwrap_err(), Error::CoreLibNotLoaded);
+    }
 }
@@ -1,13 +1,29 @@
 //! Cubism model.
 
 use crate::{
-    drawable::{DynamicDrawables, StaticDrawables},
-    parameter::StaticParameters,
-    part::StaticParts,
-    Error, Moc, Result, ALIGN_OF_MODEL, {ConstantFlags, DynamicFlags},
+    drawable::{
+        ChangedDrawables, ColoredVertex, DrawableInfo, DrawableRenderInfo, DynamicDrawable,
+        DynamicDrawables, StaticDrawable, StaticDrawableRefs, StaticDrawables,
+    },
+    parameter::{ParameterInfo, StaticParameter, StaticParameterRefs, StaticParameters},
+    part::{PartTree, StaticPart, StaticPartRefs, StaticParts},
+    Error, Moc, MocVersion, ModelData, Result, ALIGN_OF_MODEL, {ConstantFlags, DynamicFlags},
 };
 use aligned_utils::bytes::AlignedBytes;
-use std::{collections::HashMap, ffi::CStr, mem, slice};
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as HashMap;
+use alloc::{borrow::Cow, boxed::Box, string::String, vec, vec::Vec};
+use core::{
+    ffi::CStr,
+    mem,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    slice,
+};
+#[cfg(feature = "std")]
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
 
 const ISIZE_MAX: usize = isize::MAX as _;
 const I32_MAX: u32 = i32::MAX as _;
@@ -42,6 +58,12 @@ where
     })
 }
 
+/// Gets the masks of a drawable, and every mask should be the index of an existing drawable.
+#[inline]
+unsafe fn get_masks<'a>(ptr: *const u32, len: usize, count: usize) -> Option<&'a [u32]> {
+    get_slice_check(ptr, len, |(_, m)| (*m as usize) < count)
+}
+
 #[inline]
 unsafe fn get_slice_mut<'a, T>(ptr: *mut T, len: usize) -> Option<&'a mut [T]> {
     if ptr.is_null()
@@ -55,16 +77,27 @@ unsafe fn get_slice_mut<'a, T>(ptr: *mut T, len: usize) -> Option<&'a mut [T]> {
     }
 }
 
-fn init_model(moc: *const cubism_core_sys::csmMoc) -> Result<AlignedBytes> {
+#[inline]
+fn get_model_size(moc: *const cubism_core_sys::csmMoc) -> Result<usize> {
     let size = unsafe { cubism_core_sys::csmGetSizeofModel(moc) };
     if size == 0 {
+        Err(Error::InitializeModelError)
+    } else {
+        Ok(size as _)
+    }
+}
+
+fn init_model(
+    moc: *const cubism_core_sys::csmMoc,
+    mut model: AlignedBytes,
+) -> Result<AlignedBytes> {
+    let size = get_model_size(moc)?;
+    if model.len() < size || model.as_ptr().align_offset(ALIGN_OF_MODEL) != 0 {
         return Err(Error::InitializeModelError);
     }
-    let mut model = AlignedBytes::new_zeroed(size as _, ALIGN_OF_MODEL);
-    debug_assert_eq!(model.len(), size as _);
 
     unsafe {
-        if cubism_core_sys::csmInitializeModelInPlace(moc, model.as_mut_ptr().cast(), size)
+        if cubism_core_sys::csmInitializeModelInPlace(moc, model.as_mut_ptr().cast(), size as _)
             .is_null()
         {
             Err(Error::InitializeModelError)
@@ -103,15 +136,135 @@ fn get_ids_map<'a>(ids: &[&'a str]) -> HashMap<&'a str, usize> {
     ids.iter().enumerate().map(|(i, s)| (*s, i)).collect()
 }
 
+/// Returns the approximate heap size of the IDs and the map of them.
+#[inline]
+fn ids_heap_size(ids: &[&str]) -> usize {
+    mem::size_of_val(ids) + ids.len() * mem::size_of::<(&str, usize)>()
+}
+
+/// Partitions the indices of parameters by their types,
+/// and all indices are normal if the types aren't provided.
+#[inline]
+fn indices_by_type(types: Option<&[ParameterType]>, count: usize) -> (Vec<usize>, Vec<usize>) {
+    match types {
+        Some(types) => (0..count).partition(|i| types[*i].is_normal()),
+        None => ((0..count).collect(), Vec::new()),
+    }
+}
+
+/// Multiplies the opacity of a part by the opacities of all its ancestors,
+/// or returns the opacity of the part itself if its ancestors are cyclic.
+fn effective_opacity(parents: &[PartParent], opacities: &[f32], index: usize) -> f32 {
+    let mut opacity = opacities[index];
+    let mut steps = 0;
+    let mut parent = parents[index].parent();
+    while let Some(i) = parent {
+        if steps >= parents.len() {
+            return opacities[index];
+        }
+        match opacities.get(i) {
+            Some(o) => opacity *= o,
+            None => break,
+        }
+        steps += 1;
+        parent = parents[i].parent();
+    }
+
+    opacity
+}
+
+/// Same as calling [`effective_opacity`] for every part,
+/// but the opacity of every ancestor is only computed once.
+fn effective_opacities(parents: &[PartParent], opacities: &[f32]) -> Vec<f32> {
+    // `Err` marks a part whose ancestors are cyclic,
+    // so its descendants fall back to their own opacities too.
+    let mut effective: Vec<Option<core::result::Result<f32, ()>>> = vec![None; opacities.len()];
+    let mut chain = Vec::new();
+    for index in 0..opacities.len() {
+        chain.clear();
+        let mut ancestor = Ok(1.);
+        let mut current = Some(index);
+        while let Some(i) = current {
+            if let Some(o) = effective[i] {
+                ancestor = o;
+                break;
+            }
+            if chain.len() >= opacities.len() {
+                ancestor = Err(());
+                break;
+            }
+            chain.push(i);
+            current = parents[i].parent().filter(|p| *p < opacities.len());
+        }
+
+        match ancestor {
+            Ok(mut opacity) => {
+                for i in chain.iter().rev() {
+                    opacity *= opacities[*i];
+                    effective[*i] = Some(Ok(opacity));
+                }
+            }
+            Err(()) => {
+                for i in chain.iter() {
+                    effective[*i] = Some(Err(()));
+                }
+            }
+        }
+    }
+
+    effective
+        .into_iter()
+        .zip(opacities)
+        .map(|(e, o)| match e {
+            Some(Ok(e)) => e,
+            _ => *o,
+        })
+        .collect()
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn hypot(x: f32, y: f32) -> f32 {
+    x.hypot(y)
+}
+
+#[cfg(not(feature = "std"))]
+#[inline]
+fn hypot(x: f32, y: f32) -> f32 {
+    libm::hypotf(x, y)
+}
+
 #[inline]
 fn check_opacity(opacity: &f32) -> bool {
     (OPACITY_MIN..=OPACITY_MAX).contains(opacity)
 }
 
+#[inline]
+fn union_bounds(
+    bounds: Option<(Vector2, Vector2)>,
+    (min, max): (Vector2, Vector2),
+) -> (Vector2, Vector2) {
+    match bounds {
+        Some((bounds_min, bounds_max)) => (
+            Vector2::new(bounds_min.x().min(min.x()), bounds_min.y().min(min.y())),
+            Vector2::new(bounds_max.x().max(max.x()), bounds_max.y().max(max.y())),
+        ),
+        None => (min, max),
+    }
+}
+
+#[inline]
+fn get_bounds(vectors: &[Vector2]) -> Option<(Vector2, Vector2)> {
+    vectors
+        .iter()
+        .fold(None, |bounds, v| Some(union_bounds(bounds, (*v, *v))))
+}
+
 #[derive(Debug)]
 struct Parameters<'a> {
     ids: Box<[&'a str]>,
     ids_map: HashMap<&'a str, usize>,
+    types: Box<[ParameterType]>,
     min_values: &'a [f32],
     max_values: &'a [f32],
     default_values: &'a [f32],
@@ -120,6 +273,13 @@ struct Parameters<'a> {
 }
 
 impl<'a> Parameters<'a> {
+    #[inline]
+    fn heap_size(&self) -> usize {
+        ids_heap_size(&self.ids)
+            + mem::size_of_val::<[ParameterType]>(&self.types)
+            + mem::size_of_val::<[&[f32]]>(&self.key_values)
+    }
+
     unsafe fn new(model: *mut cubism_core_sys::csmModel) -> Result<Self> {
         let count = convert_i32(cubism_core_sys::csmGetParameterCount(model))
             .ok_or(Error::InvalidCount("parameter"))?;
@@ -127,6 +287,18 @@ impl<'a> Parameters<'a> {
             .ok_or(Error::GetDataError("parameter ids"))?;
         let ids_map = get_ids_map(&ids);
 
+        // `csmGetParameterTypes` is added in Cubism Core 5.0.
+        let types = if MocVersion::latest_version() >= MocVersion::Version50 {
+            get_slice(cubism_core_sys::csmGetParameterTypes(model), count)
+                .ok_or(Error::GetDataError("parameter types"))?
+                .iter()
+                .map(|t| ParameterType::from_raw(*t))
+                .collect::<Option<Box<_>>>()
+                .ok_or(Error::GetDataError("parameter types"))?
+        } else {
+            vec![ParameterType::Normal; count].into_boxed_slice()
+        };
+
         let min_values = get_slice(cubism_core_sys::csmGetParameterMinimumValues(model), count)
             .ok_or(Error::GetDataError("parameter min values"))?;
 
@@ -166,6 +338,7 @@ impl<'a> Parameters<'a> {
         Ok(Self {
             ids,
             ids_map,
+            types,
             min_values,
             max_values,
             default_values,
@@ -184,6 +357,11 @@ struct Parts<'a> {
 }
 
 impl<'a> Parts<'a> {
+    #[inline]
+    fn heap_size(&self) -> usize {
+        ids_heap_size(&self.ids)
+    }
+
     unsafe fn new(model: *mut cubism_core_sys::csmModel) -> Result<Self> {
         let count = convert_i32(cubism_core_sys::csmGetPartCount(model))
             .ok_or(Error::InvalidCount("part"))?;
@@ -221,14 +399,43 @@ struct Drawables<'a> {
     draw_orders: &'a [i32],
     render_orders: &'a [i32],
     opacities: &'a [f32],
+    /// Whether the opacities are valid, which is checked after creating and updating the model.
+    opacities_valid: bool,
+    /// The blend colors are owned defaults if the Cubism Core lib doesn't provide them.
+    multiply_colors: Cow<'a, [Vector4]>,
+    screen_colors: Cow<'a, [Vector4]>,
     marks: Box<[&'a [u32]]>,
-    vertex_positions: Box<[&'a [Vector2]]>,
+    vertex_positions: Box<[&'a mut [Vector2]]>,
     vertex_uvs: Box<[&'a [Vector2]]>,
+    /// The bounding boxes of the vertex uvs, which are static.
+    uv_bounds: Box<[Option<(Vector2, Vector2)>]>,
     indices: Box<[&'a [u16]]>,
+    /// The parent parts are owned roots if the Cubism Core lib doesn't provide them.
+    parent_part_indices: Cow<'a, [PartParent]>,
 }
 
 impl<'a> Drawables<'a> {
-    unsafe fn new(model: *const cubism_core_sys::csmModel) -> Result<Self> {
+    #[inline]
+    fn heap_size(&self) -> usize {
+        ids_heap_size(&self.ids)
+            + mem::size_of_val::<[&[u32]]>(&self.marks)
+            + mem::size_of_val::<[&mut [Vector2]]>(&self.vertex_positions)
+            + mem::size_of_val::<[&[Vector2]]>(&self.vertex_uvs)
+            + mem::size_of_val::<[Option<(Vector2, Vector2)>]>(&self.uv_bounds)
+            + mem::size_of_val::<[&[u16]]>(&self.indices)
+            + match (&self.multiply_colors, &self.screen_colors) {
+                (Cow::Owned(multiply), Cow::Owned(screen)) => {
+                    mem::size_of_val::<[Vector4]>(multiply) + mem::size_of_val::<[Vector4]>(screen)
+                }
+                _ => 0,
+            }
+            + match &self.parent_part_indices {
+                Cow::Owned(parents) => mem::size_of_val::<[PartParent]>(parents),
+                Cow::Borrowed(_) => 0,
+            }
+    }
+
+    unsafe fn new(model: *mut cubism_core_sys::csmModel) -> Result<Self> {
         let count = convert_i32(cubism_core_sys::csmGetDrawableCount(model))
             .ok_or(Error::InvalidCount("drawable"))?;
 
@@ -270,6 +477,29 @@ impl<'a> Drawables<'a> {
         )
         .ok_or(Error::GetDataError("drawable opacities"))?;
 
+        // Blend colors are added in Cubism Core 4.2.
+        let (multiply_colors, screen_colors) =
+            if MocVersion::latest_version() >= MocVersion::Version42 {
+                let multiply_colors = get_slice(
+                    cubism_core_sys::csmGetDrawableMultiplyColors(model).cast::<Vector4>(),
+                    count,
+                )
+                .ok_or(Error::GetDataError("drawable multiply colors"))?;
+
+                let screen_colors = get_slice(
+                    cubism_core_sys::csmGetDrawableScreenColors(model).cast::<Vector4>(),
+                    count,
+                )
+                .ok_or(Error::GetDataError("drawable screen colors"))?;
+
+                (Cow::Borrowed(multiply_colors), Cow::Borrowed(screen_colors))
+            } else {
+                (
+                    Cow::Owned(vec![Vector4::new(1., 1., 1., 1.); count]),
+                    Cow::Owned(vec![Vector4::new(0., 0., 0., 1.); count]),
+                )
+            };
+
         let marks = get_slice(cubism_core_sys::csmGetDrawableMaskCounts(model), count)
             .ok_or(Error::GetDataError("drawable mask counts"))?
             .iter()
@@ -280,7 +510,7 @@ impl<'a> Drawables<'a> {
                 )
                 .ok_or(Error::GetDataError("drawable masks"))?,
             )
-            .map(|(c, p)| get_slice_check(*p, convert_i32(*c)?, |(_, m)| *m <= I32_MAX))
+            .map(|(c, p)| get_masks(*p, convert_i32(*c)?, count))
             .collect::<Option<Box<_>>>()
             .ok_or(Error::GetDataError("drawable masks"))?;
 
@@ -291,12 +521,12 @@ impl<'a> Drawables<'a> {
             .iter()
             .zip(
                 get_slice(
-                    cubism_core_sys::csmGetDrawableVertexPositions(model).cast::<*const Vector2>(),
+                    cubism_core_sys::csmGetDrawableVertexPositions(model).cast::<*mut Vector2>(),
                     count,
                 )
                 .ok_or(Error::GetDataError("drawable vertex positions"))?,
             )
-            .map(|(c, p)| get_slice(*p, convert_i32(*c)?))
+            .map(|(c, p)| get_slice_mut(*p, convert_i32(*c)?))
             .collect::<Option<Box<_>>>()
             .ok_or(Error::GetDataError("drawable vertex positions"))?;
 
@@ -330,6 +560,20 @@ impl<'a> Drawables<'a> {
             })
             .collect::<Result<Box<_>>>()?;
 
+        // `csmGetDrawableParentPartIndices` is added in Cubism Core 4.2.
+        let parent_part_indices = if MocVersion::latest_version() >= MocVersion::Version42 {
+            Cow::Borrowed(
+                get_slice_check(
+                    cubism_core_sys::csmGetDrawableParentPartIndices(model).cast::<PartParent>(),
+                    count,
+                    |(_, i)| i.is_valid(),
+                )
+                .ok_or(Error::GetDataError("drawable parent part indices"))?,
+            )
+        } else {
+            Cow::Owned(vec![PartParent::new(None); count])
+        };
+
         Ok(Self {
             ids,
             ids_map,
@@ -339,10 +583,15 @@ impl<'a> Drawables<'a> {
             draw_orders,
             render_orders,
             opacities,
+            opacities_valid: true,
+            multiply_colors,
+            screen_colors,
             marks,
             vertex_positions,
+            uv_bounds: vertex_uvs.iter().map(|uvs| get_bounds(uvs)).collect(),
             vertex_uvs,
             indices,
+            parent_part_indices,
         })
     }
 }
@@ -355,23 +604,42 @@ pub struct Model<'a> {
     parameters: Parameters<'a>,
     parts: Parts<'a>,
     drawables: Drawables<'a>,
+    ignore_masks: bool,
+    global_opacity: f32,
+    /// The address of the moc when the model was created, for checking the moc isn't replaced.
+    #[cfg(debug_assertions)]
+    moc_ptr: usize,
 }
 
 impl<'a> Model<'a> {
     /// Creates [`Model`].
     pub fn new(moc: Moc) -> Result<Self> {
+        let size = get_model_size(moc.as_moc_ptr())?;
+        Self::new_in(moc, AlignedBytes::new_zeroed(size, ALIGN_OF_MODEL))
+    }
+
+    /// Creates [`Model`] in a buffer provided by the caller.
+    ///
+    /// The buffer should be aligned to `csmAlignofModel` and not smaller than the model size,
+    /// otherwise an error is returned. The buffer can be reused after calling
+    /// [`into_buffer`](Self::into_buffer).
+    pub fn new_in(moc: Moc, buffer: AlignedBytes) -> Result<Self> {
         unsafe {
-            let mut model = init_model(moc.as_moc_ptr())?;
+            let mut model = init_model(moc.as_moc_ptr(), buffer)?;
             let parameters = Parameters::new(model.as_mut_ptr().cast())?;
             let parts = Parts::new(model.as_mut_ptr().cast())?;
-            let drawables = Drawables::new(model.as_ptr().cast())?;
+            let drawables = Drawables::new(model.as_mut_ptr().cast())?;
 
             Ok(Self {
+                #[cfg(debug_assertions)]
+                moc_ptr: moc.as_moc_ptr() as usize,
                 moc,
                 model,
                 parameters,
                 parts,
                 drawables,
+                ignore_masks: false,
+                global_opacity: 1.,
             })
         }
     }
@@ -397,6 +665,166 @@ impl<'a> Model<'a> {
         Ok(new_model)
     }
 
+    /// Returns an error if the parameters or the parts of two models don't match.
+    ///
+    /// Models created from the same moc always match, otherwise their counts are compared,
+    /// and then an [`UnknownIds`](Error::UnknownIds) error with the IDs of `other` is returned
+    /// if they are different from the IDs of this model at the same indices.
+    fn check_compatible(&self, other: &Self) -> Result<()> {
+        if self.moc.as_moc_ptr() == other.moc.as_moc_ptr() {
+            return Ok(());
+        }
+
+        if other.parameter_count() != self.parameter_count() {
+            Err(Error::SliceLengthNotEqual(
+                other.parameter_count(),
+                self.parameter_count(),
+            ))
+        } else if other.part_count() != self.part_count() {
+            Err(Error::SliceLengthNotEqual(
+                other.part_count(),
+                self.part_count(),
+            ))
+        } else {
+            let unknown_ids = other
+                .parameter_ids()
+                .iter()
+                .zip(self.parameter_ids())
+                .chain(other.part_ids().iter().zip(self.part_ids()))
+                .filter(|(a, b)| a != b)
+                .map(|(a, _)| String::from(*a))
+                .collect::<Vec<_>>();
+            if unknown_ids.is_empty() {
+                Ok(())
+            } else {
+                Err(Error::UnknownIds(unknown_ids))
+            }
+        }
+    }
+
+    /// Blends the parameter values and the part opacities of two models into this model,
+    /// and then calls [`update`](Self::update).
+    ///
+    /// The result is `a * (1 - t) + b * t`.
+    /// Returns an error if the parameters or the parts of three models don't match.
+    pub fn blend_from(&mut self, a: &Self, b: &Self, t: f32) -> Result<()> {
+        self.check_compatible(a)?;
+        self.check_compatible(b)?;
+
+        let s = 1. - t;
+        for ((v, x), y) in self
+            .parameter_values_mut()
+            .iter_mut()
+            .zip(a.parameter_values())
+            .zip(b.parameter_values())
+        {
+            *v = x * s + y * t;
+        }
+        for ((o, x), y) in self
+            .part_opacities_mut()
+            .iter_mut()
+            .zip(a.part_opacities())
+            .zip(b.part_opacities())
+        {
+            *o = x * s + y * t;
+        }
+        self.update();
+
+        Ok(())
+    }
+
+    /// Copies the parameter values and the part opacities of another model into this model
+    /// without reallocation, and then calls [`update`](Self::update).
+    ///
+    /// Returns an error if the parameters or the parts of two models don't match.
+    pub fn copy_state_from(&mut self, other: &Self) -> Result<()> {
+        self.check_compatible(other)?;
+        self.parameter_values_mut()
+            .copy_from_slice(other.parameter_values());
+        self.part_opacities_mut()
+            .copy_from_slice(other.part_opacities());
+        self.update();
+
+        Ok(())
+    }
+
+    /// Consumes the model and returns its buffer, which can be reused by [`new_in`](Self::new_in).
+    #[inline]
+    pub fn into_buffer(self) -> AlignedBytes {
+        self.model
+    }
+
+    /// Returns the owned static data of the model,
+    /// which has no borrowed lifetime and can be cached.
+    #[inline]
+    pub fn snapshot(&self) -> ModelSnapshot {
+        ModelSnapshot {
+            parameters: self.static_parameters().get_all(),
+            parts: self.static_parts().get_all(),
+            drawables: self.static_drawables().get_all(),
+            canvas: self.read_canvas_info(),
+        }
+    }
+
+    /// Saves the parameter values and the part opacities of the model.
+    #[inline]
+    pub fn save_state(&self) -> ModelState {
+        ModelState {
+            parameter_values: self.parameter_values().to_vec(),
+            part_opacities: self.part_opacities().to_vec(),
+        }
+    }
+
+    /// Restores the parameter values and the part opacities from a saved state.
+    ///
+    /// Returns an error if the lengths of the state don't match the model.
+    /// It doesn't call [`update`](Self::update).
+    pub fn restore_state(&mut self, state: &ModelState) -> Result<()> {
+        if state.parameter_values.len() != self.parameter_count() {
+            Err(Error::SliceLengthNotEqual(
+                state.parameter_values.len(),
+                self.parameter_count(),
+            ))
+        } else if state.part_opacities.len() != self.part_count() {
+            Err(Error::SliceLengthNotEqual(
+                state.part_opacities.len(),
+                self.part_count(),
+            ))
+        } else {
+            self.set_parameter_values(&state.parameter_values);
+            self.set_part_opacities(&state.part_opacities);
+            Ok(())
+        }
+    }
+
+    /// Returns the indices and the differences (current - saved) of parameter values
+    /// which have been changed since the state was saved.
+    ///
+    /// Returns an error if the count of the saved parameter values doesn't match the model.
+    pub fn parameter_delta_from(&self, state: &ModelState) -> Result<Vec<(usize, f32)>> {
+        if state.parameter_values.len() != self.parameter_count() {
+            return Err(Error::SliceLengthNotEqual(
+                state.parameter_values.len(),
+                self.parameter_count(),
+            ));
+        }
+
+        Ok(self
+            .parameter_values()
+            .iter()
+            .zip(&state.parameter_values)
+            .enumerate()
+            .filter_map(|(i, (current, saved))| {
+                let delta = current - saved;
+                if delta.abs() > F32_EPSILON {
+                    Some((i, delta))
+                } else {
+                    None
+                }
+            })
+            .collect())
+    }
+
     /// Gets the model's [`Moc`]
     #[inline]
     pub fn moc(&self) -> Moc {
@@ -408,28 +836,124 @@ impl<'a> Model<'a> {
     /// The caller should make sure the returning pointer won't live longer than [`Model`].
     #[inline]
     pub fn as_model_ptr(&self) -> *const cubism_core_sys::csmModel {
+        self.check_moc();
         self.model.as_ptr().cast()
     }
 
     /// Returns a mutable point which points to [`csmModel`](cubism_core_sys::csmModel).
     ///
     /// The caller should make sure the returning pointer won't live longer than [`Model`].
+    /// If the model is updated through the pointer, [`update_only`](Self::update_only)
+    /// should be called after it, otherwise the drawable opacities aren't validated again.
     #[inline]
     pub fn as_model_mut_ptr(&mut self) -> *mut cubism_core_sys::csmModel {
+        self.check_moc();
         self.model.as_mut_ptr().cast()
     }
 
+    /// Checks the moc isn't replaced after creating the model in debug builds,
+    /// because the model and its borrowed slices depend on the moc.
+    #[inline]
+    fn check_moc(&self) {
+        #[cfg(debug_assertions)]
+        debug_assert_eq!(
+            self.moc_ptr,
+            self.moc.as_moc_ptr() as usize,
+            "the moc of the model has been replaced"
+        );
+    }
+
+    /// Returns the size of the model's moc and the size of the model.
+    #[inline]
+    pub fn sizes(&self) -> (usize, usize) {
+        (self.moc.moc_size(), self.model.len())
+    }
+
+    /// Returns the size of the model buffer, which is from `csmGetSizeofModel`.
+    #[inline]
+    pub fn model_size(&self) -> usize {
+        self.model.len()
+    }
+
+    /// Returns the approximate memory footprint of the model, including the model buffer
+    /// and the heap memory of the model's IDs, maps and slices.
+    ///
+    /// The moc isn't included because it may be shared by other models,
+    /// see [`Moc::moc_size`].
+    pub fn total_footprint(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.model_size()
+            + self.parameters.heap_size()
+            + self.parts.heap_size()
+            + self.drawables.heap_size()
+    }
+
     /// Updates the model.
     ///
     /// It should be called after setting the parameter values or the part opacities.
     ///
     /// After updating the model, the dynamic drawables may be changed.
+    ///
+    /// It's the same as calling [`reset_dynamic_flags`](Self::reset_dynamic_flags)
+    /// and then [`update_only`](Self::update_only).
     #[inline]
     pub fn update(&mut self) {
-        unsafe {
-            cubism_core_sys::csmResetDrawableDynamicFlags(self.as_model_mut_ptr());
-            cubism_core_sys::csmUpdateModel(self.as_model_mut_ptr());
+        self.reset_dynamic_flags();
+        self.update_only();
+    }
+
+    /// Resets the dynamic flags of drawables, which clears the `*_DID_CHANGE` flags.
+    ///
+    /// The `*_DID_CHANGE` flags set by [`update_only`](Self::update_only) are accumulated
+    /// until the dynamic flags are reset, so it should be called after the changes are consumed
+    /// (e.g. after uploading the buffers) and before the next [`update_only`](Self::update_only),
+    /// otherwise the changes of the previous updates are reported again.
+    #[inline]
+    pub fn reset_dynamic_flags(&mut self) {
+        unsafe { cubism_core_sys::csmResetDrawableDynamicFlags(self.as_model_mut_ptr()) }
+    }
+
+    /// Updates the model without resetting the dynamic flags of drawables.
+    ///
+    /// See [`reset_dynamic_flags`](Self::reset_dynamic_flags) for the ordering of them.
+    #[inline]
+    pub fn update_only(&mut self) {
+        unsafe { cubism_core_sys::csmUpdateModel(self.as_model_mut_ptr()) }
+        self.drawables.opacities_valid = self.drawables.opacities.iter().all(check_opacity);
+    }
+
+    /// Updates the model like [`update`](Self::update),
+    /// and returns the indices of drawables which have been changed.
+    pub fn update_and_changed(&mut self) -> ChangedDrawables {
+        self.update();
+        let mut changed = ChangedDrawables::default();
+        for (i, flags) in self.drawables.dynamic_flags.iter().enumerate() {
+            if flags.contains(DynamicFlags::VERTEX_POSITIONS_DID_CHANGE) {
+                changed.vertex_positions.push(i);
+            }
+            if flags.contains(DynamicFlags::OPACITY_DID_CHANGE) {
+                changed.opacities.push(i);
+            }
+            if flags.contains(DynamicFlags::RENDER_ORDER_DID_CHANGE) {
+                changed.render_orders.push(i);
+            }
+            if flags.contains(DynamicFlags::DRAW_ORDER_DID_CHANGE) {
+                changed.draw_orders.push(i);
+            }
         }
+
+        changed
+    }
+
+    /// Updates a batch of models in parallel like [`update`](Self::update).
+    ///
+    /// Updating a model only touches its own buffer,
+    /// so it's thread-safe to update different models at the same time.
+    #[cfg(feature = "rayon")]
+    pub fn update_batch(models: &mut [Model<'_>]) {
+        use rayon::prelude::*;
+
+        models.par_iter_mut().for_each(|m| m.update());
     }
 
     /// Reads info on the model canvas.
@@ -465,6 +989,14 @@ impl<'a> Model<'a> {
         &self.parameters.ids
     }
 
+    /// Returns all IDs of parameters in sorted order.
+    #[inline]
+    pub fn parameter_ids_sorted(&self) -> Vec<&str> {
+        let mut ids = self.parameter_ids().to_vec();
+        ids.sort_unstable();
+        ids
+    }
+
     /// Returns the index of a parameter according to its ID,
     /// or returns [`None`] if ID doesn't exist.
     #[inline]
@@ -472,6 +1004,33 @@ impl<'a> Model<'a> {
         self.parameters.ids_map.get(id.as_ref()).copied()
     }
 
+    /// Returns the types of parameters.
+    ///
+    /// Parameter types are added in Cubism Core 5.0,
+    /// so all parameters are [`Normal`](ParameterType::Normal) with an older Cubism Core lib.
+    #[inline]
+    pub fn parameter_types(&self) -> &[ParameterType] {
+        &self.parameters.types
+    }
+
+    /// Returns the indices of normal parameters and blend shape parameters,
+    /// that is `(normal, blend_shape)`.
+    ///
+    /// Parameter types are added in Cubism Core 5.0, so all indices are normal with an older
+    /// Core lib, and a warning is logged by the logger set by [`set_logger`](crate::log::set_logger).
+    pub fn parameter_indices_by_type(&self) -> (Vec<usize>, Vec<usize>) {
+        let types = if MocVersion::latest_version() >= MocVersion::Version50 {
+            Some(self.parameter_types())
+        } else {
+            crate::log::log_message(
+                "parameter types need Cubism Core 5.0, so all parameters are treated as normal",
+            );
+            None
+        };
+
+        indices_by_type(types, self.parameter_count())
+    }
+
     /// Returns the minimal values of parameters.
     #[inline]
     pub fn parameter_min_values(&self) -> &[f32] {
@@ -490,6 +1049,55 @@ impl<'a> Model<'a> {
         self.parameters.default_values
     }
 
+    /// Returns the minimal values, the maximal values and the default values of parameters.
+    #[inline]
+    pub fn parameter_ranges(&self) -> Vec<(f32, f32, f32)> {
+        self.parameter_min_values()
+            .iter()
+            .zip(self.parameter_max_values())
+            .zip(self.parameter_default_values())
+            .map(|((min, max), default)| (*min, *max, *default))
+            .collect()
+    }
+
+    /// Returns the minimal value and the maximal value of a parameter according to its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    #[inline]
+    pub fn parameter_range(&self, index: usize) -> (f32, f32) {
+        (
+            self.parameter_min_values()[index],
+            self.parameter_max_values()[index],
+        )
+    }
+
+    /// Returns the minimal value and the maximal value of a parameter according to its ID,
+    /// or returns [`None`] if ID doesn't exist.
+    #[inline]
+    pub fn parameter_range_by_id<T: AsRef<str>>(&self, id: T) -> Option<(f32, f32)> {
+        Some(self.parameter_range(self.parameter_index(id)?))
+    }
+
+    /// Returns the range and the current value of a parameter according to its index.
+    ///
+    /// See [`parameter_info`](Self::parameter_info) for the static data of a parameter by ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    #[inline]
+    pub fn parameter_current_info(&self, index: usize) -> ParameterInfo {
+        let (min_value, max_value) = self.parameter_range(index);
+        ParameterInfo {
+            min_value,
+            max_value,
+            default_value: self.parameter_default_values()[index],
+            value: self.parameter_values()[index],
+        }
+    }
+
     /// Returns the values of parameters.
     #[inline]
     pub fn parameter_values(&self) -> &[f32] {
@@ -513,6 +1121,58 @@ impl<'a> Model<'a> {
         self.parameter_values_mut().copy_from_slice(values.as_ref());
     }
 
+    /// Set the values of parameters.
+    ///
+    /// Returns an error if the length of values doesn't match the returning count of
+    /// [`parameter_count`](Self::parameter_count).
+    #[inline]
+    pub fn try_set_parameter_values<T: AsRef<[f32]>>(&mut self, values: T) -> Result<()> {
+        let values = values.as_ref();
+        if values.len() != self.parameter_count() {
+            Err(Error::SliceLengthNotEqual(
+                values.len(),
+                self.parameter_count(),
+            ))
+        } else {
+            self.set_parameter_values(values);
+            Ok(())
+        }
+    }
+
+    /// Resets the values of parameters to their default values.
+    ///
+    /// It doesn't call [`update`](Self::update).
+    #[inline]
+    pub fn reset_parameter_values(&mut self) {
+        self.parameters
+            .values
+            .copy_from_slice(self.parameters.default_values);
+    }
+
+    /// Set the values of parameters to their minimal values.
+    #[inline]
+    pub fn set_parameters_to_min(&mut self) {
+        self.parameters
+            .values
+            .copy_from_slice(self.parameters.min_values);
+    }
+
+    /// Set the values of parameters to their maximal values.
+    #[inline]
+    pub fn set_parameters_to_max(&mut self) {
+        self.parameters
+            .values
+            .copy_from_slice(self.parameters.max_values);
+    }
+
+    /// Returns the value of a parameter according to its ID,
+    /// or returns [`None`] if ID doesn't exist.
+    #[inline]
+    pub fn parameter_value<T: AsRef<str>>(&self, id: T) -> Option<f32> {
+        let index = self.parameter_index(id)?;
+        self.parameter_values().get(index).copied()
+    }
+
     /// Set the value of a parameter according to its ID.
     ///
     /// # Panics
@@ -552,25 +1212,213 @@ impl<'a> Model<'a> {
         mem::replace(self.parameter_values_mut().get_unchecked_mut(index), value)
     }
 
-    /// Returns the key values of parameters.
+    /// Set the value of a parameter according to its ID,
+    /// clamping the value to the minimal and maximal values of the parameter.
+    ///
+    /// Returns the clamped value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ID doesn't exist.
     #[inline]
-    pub fn parameter_key_values(&self) -> &[&[f32]] {
-        &self.parameters.key_values
+    pub fn set_parameter_value_clamped<T: AsRef<str>>(&mut self, id: T, value: f32) -> f32 {
+        self.set_parameter_value_index_clamped(
+            self.parameter_index(id.as_ref())
+                .unwrap_or_else(|| panic!("ID {} doesn't exist", id.as_ref())),
+            value,
+        )
     }
 
-    /// Returns static parameters.
+    /// Set the value of a parameter according to its index,
+    /// clamping the value to the minimal and maximal values of the parameter.
+    ///
+    /// Returns the clamped value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
     #[inline]
-    pub fn static_parameters(&self) -> StaticParameters {
-        StaticParameters::new(self)
+    pub fn set_parameter_value_index_clamped(&mut self, index: usize, value: f32) -> f32 {
+        let value = value
+            .max(self.parameter_min_values()[index])
+            .min(self.parameter_max_values()[index]);
+        self.parameter_values_mut()[index] = value;
+        value
     }
 
-    /// Returns the count of parts.
-    #[inline]
-    pub fn part_count(&self) -> usize {
-        self.parts.ids.len()
+    /// Blends the target values into the values of parameters by `weight`,
+    /// that is `value * (1 - weight) + target * weight`,
+    /// clamping the results to the minimal and maximal values of parameters.
+    ///
+    /// Returns an error if the length of targets doesn't match the returning count of
+    /// [`parameter_count`](Self::parameter_count).
+    pub fn blend_parameter_values<T: AsRef<[f32]>>(
+        &mut self,
+        targets: T,
+        weight: f32,
+    ) -> Result<()> {
+        let targets = targets.as_ref();
+        if targets.len() != self.parameter_count() {
+            return Err(Error::SliceLengthNotEqual(
+                targets.len(),
+                self.parameter_count(),
+            ));
+        }
+        for (index, target) in targets.iter().enumerate() {
+            let value = self.parameter_values()[index];
+            self.set_parameter_value_index_clamped(index, value + (target - value) * weight);
+        }
+
+        Ok(())
     }
 
-    /// Returns all IDs of parts.
+    /// Blends the target value into the value of a parameter according to its ID by `weight`,
+    /// clamping the result to the minimal and maximal values of the parameter.
+    ///
+    /// Returns the blended value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ID doesn't exist.
+    #[inline]
+    pub fn blend_parameter_value<T: AsRef<str>>(&mut self, id: T, target: f32, weight: f32) -> f32 {
+        let index = self
+            .parameter_index(id.as_ref())
+            .unwrap_or_else(|| panic!("ID {} doesn't exist", id.as_ref()));
+        let value = self.parameter_values()[index];
+        self.set_parameter_value_index_clamped(index, value + (target - value) * weight)
+    }
+
+    /// Sets the values of parameters according to their IDs from a map of IDs to values,
+    /// e.g. an expression or a pose.
+    ///
+    /// All entries with existing IDs are applied, and then an [`UnknownIds`](Error::UnknownIds)
+    /// error with all IDs which don't exist is returned if there is any.
+    pub fn set_parameter_values_from_map<S, M>(&mut self, map: M) -> Result<()>
+    where
+        S: AsRef<str>,
+        M: IntoIterator<Item = (S, f32)>,
+    {
+        let mut unknown_ids = Vec::new();
+        for (id, value) in map {
+            match self.parameter_index(id.as_ref()) {
+                Some(index) => self.parameter_values_mut()[index] = value,
+                None => unknown_ids.push(id.as_ref().into()),
+            }
+        }
+
+        if unknown_ids.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::UnknownIds(unknown_ids))
+        }
+    }
+
+    /// Returns the value of a parameter according to its index, clamped into `[lo, hi]`.
+    ///
+    /// The range `[lo, hi]` is intersected with the minimal and maximal values of the parameter.
+    /// If the intersection is empty, the upper bound of it is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    #[inline]
+    pub fn parameter_value_clamped_to(&self, index: usize, lo: f32, hi: f32) -> f32 {
+        let min = self.parameter_min_values()[index].max(lo);
+        let max = self.parameter_max_values()[index].min(hi);
+        self.parameter_values()[index].max(min).min(max)
+    }
+
+    /// Returns `true` if any parameter value is out of the range of its minimal and maximal values.
+    #[inline]
+    pub fn has_out_of_range_parameters(&self) -> bool {
+        self.parameter_values()
+            .iter()
+            .zip(self.parameter_min_values())
+            .zip(self.parameter_max_values())
+            .any(|((v, min), max)| !(min - F32_EPSILON..=max + F32_EPSILON).contains(v))
+    }
+
+    /// Returns `true` if the value of a parameter equals its default value
+    /// within a small tolerance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    #[inline]
+    pub fn parameter_is_default(&self, index: usize) -> bool {
+        (self.parameter_values()[index] - self.parameter_default_values()[index]).abs()
+            <= F32_EPSILON
+    }
+
+    /// Returns the indices of parameters whose values don't equal their default values.
+    pub fn changed_parameter_indices(&self) -> Vec<usize> {
+        (0..self.parameter_count())
+            .filter(|i| !self.parameter_is_default(*i))
+            .collect()
+    }
+
+    /// Returns the key values of parameters.
+    #[inline]
+    pub fn parameter_key_values(&self) -> &[&[f32]] {
+        &self.parameters.key_values
+    }
+
+    /// Returns the key values of all parameters in a flattened vector,
+    /// with the offsets where the key values of each parameter begin.
+    ///
+    /// The key values of the `i`-th parameter are `values[offsets[i]..offsets[i + 1]]`,
+    /// or `values[offsets[i]..]` for the last parameter.
+    pub fn flattened_key_values(&self) -> (Vec<f32>, Vec<usize>) {
+        let key_values = self.parameter_key_values();
+        let mut values = Vec::with_capacity(key_values.iter().map(|k| k.len()).sum());
+        let mut offsets = Vec::with_capacity(key_values.len());
+        for k in key_values {
+            offsets.push(values.len());
+            values.extend_from_slice(k);
+        }
+
+        (values, offsets)
+    }
+
+    /// Returns the indices of parameters whose key values satisfy `pred`.
+    #[inline]
+    pub fn parameters_where_keys<F: Fn(&[f32]) -> bool>(&self, pred: F) -> Vec<usize> {
+        self.parameter_key_values()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, k)| if pred(k) { Some(i) } else { None })
+            .collect()
+    }
+
+    /// Returns the static parameter according to its ID,
+    /// or returns [`None`] if ID doesn't exist.
+    #[inline]
+    pub fn parameter_info<T: AsRef<str>>(&self, id: T) -> Option<StaticParameter> {
+        let index = self.parameter_index(id)?;
+        // SAFETY: the index from hashmap is never out of bound.
+        unsafe { Some(self.static_parameters().get_index_unchecked(index)) }
+    }
+
+    /// Returns static parameters.
+    #[inline]
+    pub fn static_parameters(&self) -> StaticParameters {
+        StaticParameters::new(self)
+    }
+
+    /// Returns static parameters which borrow their data from the model.
+    #[inline]
+    pub fn static_parameter_refs(&self) -> StaticParameterRefs<'_> {
+        StaticParameterRefs::new(self)
+    }
+
+    /// Returns the count of parts.
+    #[inline]
+    pub fn part_count(&self) -> usize {
+        self.parts.ids.len()
+    }
+
+    /// Returns all IDs of parts.
     #[inline]
     pub fn part_ids(&self) -> &[&str] {
         &self.parts.ids
@@ -607,6 +1455,58 @@ impl<'a> Model<'a> {
             .copy_from_slice(opacities.as_ref());
     }
 
+    /// Set the opacities of parts.
+    ///
+    /// Returns an error if the length of opacities doesn't match the returning count of
+    /// [`part_count`](Self::part_count).
+    #[inline]
+    pub fn try_set_part_opacities<T: AsRef<[f32]>>(&mut self, opacities: T) -> Result<()> {
+        let opacities = opacities.as_ref();
+        if opacities.len() != self.part_count() {
+            Err(Error::SliceLengthNotEqual(
+                opacities.len(),
+                self.part_count(),
+            ))
+        } else {
+            self.set_part_opacities(opacities);
+            Ok(())
+        }
+    }
+
+    /// Set the opacities of all parts to the same value.
+    ///
+    /// The opacity is clamped to `[0, 1]`.
+    #[inline]
+    pub fn set_all_part_opacities(&mut self, opacity: f32) {
+        self.part_opacities_mut().fill(opacity.clamp(0., 1.));
+    }
+
+    /// Resets the opacities of all parts to 1.
+    ///
+    /// It doesn't call [`update`](Self::update).
+    #[inline]
+    pub fn reset_part_opacities(&mut self) {
+        self.part_opacities_mut().fill(1.);
+    }
+
+    /// Multiplies the opacities of all parts by a factor.
+    ///
+    /// The opacities are clamped to `[0, 1]`.
+    #[inline]
+    pub fn scale_part_opacities(&mut self, factor: f32) {
+        for opacity in self.part_opacities_mut() {
+            *opacity = (*opacity * factor).clamp(0., 1.);
+        }
+    }
+
+    /// Returns the opacity of a part according to its ID,
+    /// or returns [`None`] if ID doesn't exist.
+    #[inline]
+    pub fn part_opacity<T: AsRef<str>>(&self, id: T) -> Option<f32> {
+        let index = self.part_index(id)?;
+        self.part_opacities().get(index).copied()
+    }
+
     /// Set the opacity of a part according to its ID.
     ///
     /// # Panics
@@ -646,18 +1546,135 @@ impl<'a> Model<'a> {
         mem::replace(self.part_opacities_mut().get_unchecked_mut(index), opacity)
     }
 
+    /// Returns the indices of parts which are fully hidden, i.e. whose opacities are almost zero.
+    #[inline]
+    pub fn hidden_parts(&self) -> Vec<usize> {
+        self.part_opacities()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, o)| if *o < F32_EPSILON { Some(i) } else { None })
+            .collect()
+    }
+
     /// Returns the parent index of a part.
     #[inline]
     pub fn part_parent(&self) -> &[PartParent] {
         self.parts.parent_indices
     }
 
+    /// Returns the indices of the children of a part according to its index.
+    ///
+    /// See [`part_tree`](Self::part_tree) for the children of all parts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    pub fn part_children(&self, index: usize) -> Vec<usize> {
+        assert!(index < self.part_count());
+        self.part_parent()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| {
+                if i != index && p.parent() == Some(index) {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the tree of parts, which precomputes the children of all parts.
+    #[inline]
+    pub fn part_tree(&self) -> PartTree {
+        PartTree::new(self.part_parent())
+    }
+
+    /// Returns an iterator over the IDs, the opacities and the parent indices of parts.
+    #[inline]
+    pub fn part_rows(&self) -> impl Iterator<Item = (&str, f32, PartParent)> + '_ {
+        self.part_ids()
+            .iter()
+            .zip(self.part_opacities())
+            .zip(self.part_parent())
+            .map(|((id, opacity), parent)| (*id, *opacity, *parent))
+    }
+
+    /// Returns the depth of a part in the part hierarchy according to its index.
+    ///
+    /// The depth of a root part is 0. For malformed cyclic parents,
+    /// the depth is at most [`part_count`](Self::part_count).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    pub fn part_depth(&self, index: usize) -> usize {
+        let parents = self.part_parent();
+        let mut depth = 0;
+        let mut parent = parents[index].parent();
+        while let Some(i) = parent {
+            if depth >= parents.len() {
+                break;
+            }
+            depth += 1;
+            parent = parents.get(i).and_then(PartParent::parent);
+        }
+
+        depth
+    }
+
+    /// Returns the deepest depth of parts in the part hierarchy.
+    #[inline]
+    pub fn max_part_depth(&self) -> usize {
+        (0..self.part_count())
+            .map(|i| self.part_depth(i))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the opacity of a part multiplied by the opacities of all its ancestors
+    /// according to its index.
+    ///
+    /// For malformed cyclic parents, the opacity of the part itself is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    pub fn effective_part_opacity(&self, index: usize) -> f32 {
+        effective_opacity(self.part_parent(), self.part_opacities(), index)
+    }
+
+    /// Returns the effective opacities of all parts.
+    ///
+    /// It's the same as calling [`effective_part_opacity`](Self::effective_part_opacity)
+    /// for every part, but the opacity of every ancestor is only computed once.
+    pub fn effective_part_opacities(&self) -> Vec<f32> {
+        effective_opacities(self.part_parent(), self.part_opacities())
+    }
+
+    /// Returns the count of parts which are the parent of any part.
+    pub fn branching_part_count(&self) -> usize {
+        let mut is_parent = vec![false; self.part_count()];
+        for parent in self.part_parent().iter().filter_map(|p| p.parent()) {
+            if let Some(p) = is_parent.get_mut(parent) {
+                *p = true;
+            }
+        }
+        is_parent.into_iter().filter(|p| *p).count()
+    }
+
     /// Returns static parts.
     #[inline]
     pub fn static_parts(&self) -> StaticParts {
         StaticParts::new(self)
     }
 
+    /// Returns static parts which borrow their data from the model.
+    #[inline]
+    pub fn static_part_refs(&self) -> StaticPartRefs<'_> {
+        StaticPartRefs::new(self)
+    }
+
     /// Returns the count of drawables.
     #[inline]
     pub fn drawable_count(&self) -> usize {
@@ -683,24 +1700,61 @@ impl<'a> Model<'a> {
         self.drawables.constant_flags
     }
 
+    /// Returns the raw bits of the constant flags of a drawable according to its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    #[inline]
+    pub fn drawable_constant_flags_raw(&self, index: usize) -> u8 {
+        self.drawables.constant_flags[index].bits()
+    }
+
     /// Returns the dynamic flags of drawables.
     ///
     /// The dynamic flags may be changed after calling [`update`](Self::update).
     #[inline]
     pub fn drawable_dynamic_flags(&self) -> Result<&[DynamicFlags]> {
-        if self.drawables.dynamic_flags.iter().all(|f| f.is_valid()) {
-            Ok(self.drawables.dynamic_flags)
-        } else {
-            Err(Error::InvalidFlags("dynamic"))
+        match self.drawables.dynamic_flags.iter().find(|f| !f.is_valid()) {
+            Some(f) => Err(Error::InvalidFlags("dynamic", f.bits())),
+            None => Ok(self.drawables.dynamic_flags),
         }
     }
 
+    /// Returns the raw bits of the dynamic flags of a drawable according to its index.
+    ///
+    /// Unlike [`drawable_dynamic_flags`](Self::drawable_dynamic_flags), the bits aren't validated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    #[inline]
+    pub fn drawable_dynamic_flags_raw(&self, index: usize) -> u8 {
+        self.drawables.dynamic_flags[index].bits()
+    }
+
     /// Returns the texture indices of drawables.
     #[inline]
     pub fn drawable_texture_indices(&self) -> &[u32] {
         self.drawables.texture_indices
     }
 
+    /// Returns the indices of drawables whose texture indices are in `[lo, hi)`.
+    #[inline]
+    pub fn drawables_in_texture_range(&self, lo: usize, hi: usize) -> Vec<usize> {
+        self.drawable_texture_indices()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, t)| {
+                if (lo..hi).contains(&(*t as usize)) {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
     /// Returns the draw orders of drawables.
     ///
     /// The draw orders may be changed after calling [`update`](Self::update).
@@ -720,195 +1774,2565 @@ impl<'a> Model<'a> {
     /// Returns the opacities of drawables.
     ///
     /// The opacities may be changed after calling [`update`](Self::update).
+    /// They are validated only once after creating or updating the model.
     #[inline]
     pub fn drawable_opacities(&self) -> Result<&[f32]> {
-        if self.drawables.opacities.iter().all(|o| check_opacity(o)) {
+        if self.drawables.opacities_valid {
             Ok(self.drawables.opacities)
         } else {
             Err(Error::GetDataError("drawable opacities"))
         }
     }
 
-    /// Returns the masks of drawables.
+    /// Sets the global opacity of the model, which is 1 by default.
+    ///
+    /// It only affects [`effective_drawable_opacity`](Self::effective_drawable_opacity),
+    /// and the opacities of the model aren't changed.
     #[inline]
-    pub fn drawable_masks(&self) -> &[&[u32]] {
-        &self.drawables.marks
+    pub fn set_global_opacity(&mut self, opacity: f32) {
+        self.global_opacity = opacity;
     }
 
-    /// Returns the vertex positions of drawables.
-    ///
-    /// The vertex positions may be changed after calling [`update`](Self::update).
+    /// Returns the global opacity of the model.
     #[inline]
-    pub fn drawable_vertex_positions(&self) -> &[&[Vector2]] {
-        &self.drawables.vertex_positions
+    pub fn global_opacity(&self) -> f32 {
+        self.global_opacity
     }
 
-    /// Returns the vertex uvs of drawables.
+    /// Returns the opacity of a drawable multiplied by the global opacity
+    /// according to its index, which is clamped to `[0, 1]`.
+    ///
+    /// Returns an error if the opacities are invalid, see [`drawable_opacities`](Self::drawable_opacities).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
     #[inline]
-    pub fn drawable_vertex_uvs(&self) -> &[&[Vector2]] {
-        &self.drawables.vertex_uvs
+    pub fn effective_drawable_opacity(&self, index: usize) -> Result<f32> {
+        Ok((self.drawable_opacities()?[index] * self.global_opacity).clamp(0., 1.))
     }
 
-    /// Returns the indices of drawables.
+    /// Returns `false` if a drawable isn't visible or its opacity is nearly zero
+    /// according to its index, which means it can be skipped when rendering.
+    ///
+    /// Returns an error if the dynamic flags or the opacities are invalid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
     #[inline]
-    pub fn drawable_indices(&self) -> &[&[u16]] {
-        &self.drawables.indices
+    pub fn should_render(&self, index: usize) -> Result<bool> {
+        Ok(
+            self.drawable_dynamic_flags()?[index].contains(DynamicFlags::IS_VISIBLE)
+                && self.drawable_opacities()?[index] > F32_EPSILON,
+        )
     }
 
-    /// Returns static drawables.
-    #[inline]
-    pub fn static_drawables(&self) -> StaticDrawables {
-        StaticDrawables::new(self)
+    /// Returns the indices of drawables which should be rendered,
+    /// see [`should_render`](Self::should_render).
+    pub fn renderable_drawable_indices(&self) -> Result<Vec<usize>> {
+        let dynamic_flags = self.drawable_dynamic_flags()?;
+        let opacities = self.drawable_opacities()?;
+
+        Ok((0..self.drawable_count())
+            .filter(|i| {
+                dynamic_flags[*i].contains(DynamicFlags::IS_VISIBLE) && opacities[*i] > F32_EPSILON
+            })
+            .collect())
     }
 
-    /// Returns dynamic drawables.
+    /// Returns the multiply colors of drawables.
+    ///
+    /// The multiply colors may be changed after calling [`update`](Self::update).
+    /// They are added in Cubism Core 4.2, and they are always white with an older Core lib.
     #[inline]
-    pub fn dynamic_drawables(&self) -> DynamicDrawables {
-        DynamicDrawables::new(self)
+    pub fn drawable_multiply_colors(&self) -> &[Vector4] {
+        &self.drawables.multiply_colors
     }
-}
-
-/// Two dimension vector.
-#[repr(transparent)]
-#[derive(Clone, Copy, Debug)]
-pub struct Vector2(cubism_core_sys::csmVector2);
 
-impl Vector2 {
-    /// Creates [`Vector2`]
+    /// Returns the screen colors of drawables.
+    ///
+    /// The screen colors may be changed after calling [`update`](Self::update).
+    /// They are added in Cubism Core 4.2, and they are always black with an older Core lib.
     #[inline]
-    pub fn new(x: f32, y: f32) -> Self {
-        Self(cubism_core_sys::csmVector2 { X: x, Y: y })
+    pub fn drawable_screen_colors(&self) -> &[Vector4] {
+        &self.drawables.screen_colors
     }
 
-    /// Returns the x value of a vector.
+    /// Returns the tint of a drawable according to its index,
+    /// which is the multiply color of the drawable scaled by its opacity.
+    ///
+    /// It's a rough approximation of the color of a drawable.
+    /// Returns an error if the opacities are invalid, see [`drawable_opacities`](Self::drawable_opacities).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
     #[inline]
-    pub fn x(&self) -> f32 {
-        self.0.X
+    pub fn drawable_screen_tint(&self, index: usize) -> Result<Vector4> {
+        let opacity = self.drawable_opacities()?[index];
+        let (r, g, b, a) = self.drawable_multiply_colors()[index].x_y_z_w();
+        Ok(Vector4::new(
+            r * opacity,
+            g * opacity,
+            b * opacity,
+            a * opacity,
+        ))
     }
 
-    /// Returns the y value of a vector.
+    /// Returns the masks of drawables.
     #[inline]
-    pub fn y(&self) -> f32 {
-        self.0.Y
+    pub fn drawable_masks(&self) -> &[&[u32]] {
+        &self.drawables.marks
     }
 
-    /// Returns the x value and y value of a vector.
+    /// Sets whether to ignore the masks of drawables when rendering.
+    ///
+    /// It's a render-time override for renderers which can't do masking,
+    /// and it only affects [`effective_masks`](Self::effective_masks).
+    /// The mask data of the model isn't changed.
     #[inline]
-    pub fn x_y(&self) -> (f32, f32) {
-        (self.0.X, self.0.Y)
+    pub fn set_ignore_masks(&mut self, ignore: bool) {
+        self.ignore_masks = ignore;
     }
-}
 
-impl Default for Vector2 {
+    /// Returns `true` if the masks of drawables are ignored when rendering.
     #[inline]
-    fn default() -> Self {
-        Self(cubism_core_sys::csmVector2 { X: 0., Y: 0. })
+    pub fn ignore_masks(&self) -> bool {
+        self.ignore_masks
     }
-}
 
-impl PartialEq for Vector2 {
+    /// Returns the masks of a drawable for rendering according to its index.
+    ///
+    /// Returns an empty slice if the masks are ignored by [`set_ignore_masks`](Self::set_ignore_masks).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
     #[inline]
-    fn eq(&self, other: &Self) -> bool {
-        self.0.X == other.0.X && self.0.Y == other.0.Y
+    pub fn effective_masks(&self, index: usize) -> &[u32] {
+        let masks = self.drawable_masks()[index];
+        if self.ignore_masks {
+            &[]
+        } else {
+            masks
+        }
     }
-}
 
-impl From<cubism_core_sys::csmVector2> for Vector2 {
+    /// Returns `true` if a drawable is used as a mask by any other drawable.
     #[inline]
-    fn from(vector: cubism_core_sys::csmVector2) -> Self {
-        Self(vector)
+    pub fn is_mask_drawable(&self, index: usize) -> bool {
+        self.drawable_masks()
+            .iter()
+            .enumerate()
+            .any(|(i, masks)| i != index && masks.iter().any(|m| *m as usize == index))
     }
-}
 
-impl From<Vector2> for cubism_core_sys::csmVector2 {
+    /// Returns the indices of drawables which are used as masks by other drawables.
+    pub fn mask_drawables(&self) -> Vec<usize> {
+        let mut is_mask = vec![false; self.drawable_count()];
+        for (i, masks) in self.drawable_masks().iter().enumerate() {
+            for m in masks.iter().map(|m| *m as usize).filter(|m| *m != i) {
+                if let Some(is_mask) = is_mask.get_mut(m) {
+                    *is_mask = true;
+                }
+            }
+        }
+
+        is_mask
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, is_mask)| if is_mask { Some(i) } else { None })
+            .collect()
+    }
+
+    /// Returns an iterator of the indices of drawables which are masked by other drawables.
+    ///
+    /// It doesn't allocate, see [`masked_drawable_indices`](Self::masked_drawable_indices)
+    /// for collecting the indices.
     #[inline]
-    fn from(vector: Vector2) -> Self {
-        vector.0
+    pub fn masked_drawables(&self) -> impl Iterator<Item = usize> + '_ {
+        self.drawable_masks()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, masks)| if masks.is_empty() { None } else { Some(i) })
     }
-}
 
-/// The parent index of a part.
-///
-/// A part has a parent, or it is a root.
-#[repr(transparent)]
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-pub struct PartParent(i32);
+    /// Returns the indices of drawables which are masked by other drawables.
+    #[inline]
+    pub fn masked_drawable_indices(&self) -> Vec<usize> {
+        self.masked_drawables().collect()
+    }
 
-impl PartParent {
-    /// The parent index of a part when the part is a root.
-    pub const ROOT: i32 = -1;
+    /// Returns `true` if a drawable is masked by other drawables and its masks are inverted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    #[inline]
+    pub fn is_inverted_masked(&self, index: usize) -> bool {
+        !self.drawable_masks()[index].is_empty()
+            && self.drawables.constant_flags[index].contains(ConstantFlags::IS_INVERTED_MASK)
+    }
 
-    /// Creates [`PartParent`].
+    /// Returns an iterator of the indices of masked drawables whose masks are inverted.
+    #[inline]
+    pub fn inverted_masked_drawables(&self) -> impl Iterator<Item = usize> + '_ {
+        self.masked_drawables().filter(move |i| {
+            self.drawables.constant_flags[*i].contains(ConstantFlags::IS_INVERTED_MASK)
+        })
+    }
+
+    /// Returns the vertex positions of drawables.
+    ///
+    /// The vertex positions may be changed after calling [`update`](Self::update).
+    #[inline]
+    pub fn drawable_vertex_positions(&self) -> &[&[Vector2]] {
+        let positions: &[&mut [Vector2]] = &self.drawables.vertex_positions;
+        // SAFETY: `&mut [Vector2]` and `&[Vector2]` have the same layout,
+        // and the returned shared references can't be used to mutate the positions.
+        unsafe { &*(positions as *const [&mut [Vector2]] as *const [&[Vector2]]) }
+    }
+
+    /// Returns the mutable vertex positions of a drawable according to its index,
+    /// or returns [`None`] if the index is out of bound.
+    ///
+    /// It's for overriding the deformed vertex positions before rendering.
+    /// The written positions may be overwritten by the next [`update`](Self::update),
+    /// which rewrites the positions of the drawables whose
+    /// [`VERTEX_POSITIONS_DID_CHANGE`](DynamicFlags::VERTEX_POSITIONS_DID_CHANGE) is raised,
+    /// so they should be written again after every update.
+    #[inline]
+    pub fn drawable_vertex_positions_mut(&mut self, index: usize) -> Option<&mut [Vector2]> {
+        self.drawables
+            .vertex_positions
+            .get_mut(index)
+            .map(|positions| &mut **positions)
+    }
+
+    /// Returns the vertex counts of drawables.
+    #[inline]
+    pub fn drawable_vertex_counts(&self) -> Vec<usize> {
+        self.drawable_vertex_positions()
+            .iter()
+            .map(|p| p.len())
+            .collect()
+    }
+
+    /// Returns the vertex count of a drawable according to its index.
     ///
     /// # Panics
     ///
-    /// Panics if the parent index is less than [`ROOT`](Self::ROOT).
+    /// Panics if the index is out of bound.
     #[inline]
-    pub fn new(parent_index: Option<usize>) -> Self {
-        match parent_index {
-            Some(i) => {
-                assert!(i <= i32::MAX as _);
-                Self(i as _)
-            }
-            None => Self(Self::ROOT),
+    pub fn drawable_vertex_count(&self, index: usize) -> usize {
+        self.drawable_vertex_positions()[index].len()
+    }
+
+    /// Returns the differences (current - previous) of the vertex positions of a drawable
+    /// according to its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound or the length of `prev` doesn't match
+    /// the vertex count of the drawable.
+    pub fn vertex_deltas_from(&self, index: usize, prev: &[Vector2]) -> Vec<Vector2> {
+        let positions = self.drawable_vertex_positions()[index];
+        assert_eq!(positions.len(), prev.len());
+        positions
+            .iter()
+            .zip(prev)
+            .map(|(current, prev)| *current - *prev)
+            .collect()
+    }
+
+    /// Returns the maximal distance between the current vertex positions
+    /// and the previous vertex positions of all drawables.
+    ///
+    /// `prev` should have the same layout as [`drawable_vertex_positions`](Self::drawable_vertex_positions).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layout of `prev` doesn't match the vertex positions of drawables.
+    pub fn max_vertex_displacement(&self, prev: &[Box<[Vector2]>]) -> f32 {
+        let positions = self.drawable_vertex_positions();
+        assert_eq!(positions.len(), prev.len());
+        positions
+            .iter()
+            .zip(prev)
+            .flat_map(|(current, prev)| {
+                assert_eq!(current.len(), prev.len());
+                current.iter().zip(prev.iter())
+            })
+            .map(|(current, prev)| (*current - *prev).length())
+            .fold(0., f32::max)
+    }
+
+    /// Returns the vertex uvs of drawables.
+    #[inline]
+    pub fn drawable_vertex_uvs(&self) -> &[&[Vector2]] {
+        &self.drawables.vertex_uvs
+    }
+
+    /// Returns the axis-aligned bounding box `(min, max)` of the vertex uvs of a drawable
+    /// according to its index, or returns [`None`] if the drawable has no vertex.
+    ///
+    /// The bounding boxes are computed once when creating the model because the uvs are static.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    #[inline]
+    pub fn drawable_uv_bounds(&self, index: usize) -> Option<(Vector2, Vector2)> {
+        self.drawables.uv_bounds[index]
+    }
+
+    /// Returns the bounding boxes of the vertex uvs of all drawables,
+    /// see [`drawable_uv_bounds`](Self::drawable_uv_bounds).
+    #[inline]
+    pub fn drawable_uv_bounds_all(&self) -> &[Option<(Vector2, Vector2)>] {
+        &self.drawables.uv_bounds
+    }
+
+    /// Returns the indices of drawables.
+    #[inline]
+    pub fn drawable_indices(&self) -> &[&[u16]] {
+        &self.drawables.indices
+    }
+
+    /// Returns an iterator over the vertex indices of the triangles of a drawable
+    /// according to its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    #[inline]
+    pub fn drawable_triangles(&self, index: usize) -> impl Iterator<Item = [usize; 3]> + '_ {
+        self.drawable_indices()[index]
+            .chunks_exact(3)
+            .map(|t| [t[0] as usize, t[1] as usize, t[2] as usize])
+    }
+
+    /// Returns the parent part indices of drawables.
+    ///
+    /// They are read by `csmGetDrawableParentPartIndices`, which is added in Cubism Core 4.2,
+    /// so every drawable is a root with an older Core lib.
+    #[inline]
+    pub fn drawable_parent_parts(&self) -> &[PartParent] {
+        &self.drawables.parent_part_indices
+    }
+
+    /// Returns a map from the IDs of drawables to the IDs of their parent parts,
+    /// or [`None`] if a drawable doesn't belong to any part.
+    pub fn drawable_part_id_map(&self) -> HashMap<&str, Option<&str>> {
+        let part_ids = self.part_ids();
+        self.drawable_ids()
+            .iter()
+            .zip(self.drawable_parent_parts())
+            .map(|(id, parent)| (*id, parent.parent().and_then(|p| part_ids.get(p).copied())))
+            .collect()
+    }
+
+    /// Returns the indices of drawables which don't belong to any part.
+    ///
+    /// It's based on [`drawable_parent_parts`](Self::drawable_parent_parts),
+    /// so all drawables are returned with a Cubism Core lib older than 4.2.
+    #[inline]
+    pub fn orphan_drawables(&self) -> Vec<usize> {
+        self.drawable_parent_parts()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| if p.is_root() { Some(i) } else { None })
+            .collect()
+    }
+
+    /// Returns static drawables which borrow their data from the model.
+    #[inline]
+    pub fn static_drawable_refs(&self) -> StaticDrawableRefs<'_> {
+        StaticDrawableRefs::new(self)
+    }
+
+    /// Returns static drawables.
+    #[inline]
+    pub fn static_drawables(&self) -> StaticDrawables {
+        StaticDrawables::new(self)
+    }
+
+    /// Returns dynamic drawables.
+    #[inline]
+    pub fn dynamic_drawables(&self) -> DynamicDrawables {
+        DynamicDrawables::new(self)
+    }
+
+    /// Returns the dynamic drawable according to its ID,
+    /// or returns [`None`] if ID doesn't exist.
+    ///
+    /// Only the data of this drawable is copied.
+    #[inline]
+    pub fn dynamic_drawable<T: AsRef<str>>(&self, id: T) -> Option<Result<DynamicDrawable>> {
+        let index = self.drawable_index(id)?;
+        // SAFETY: the index from hashmap is never out of bound.
+        unsafe { Some(self.dynamic_drawables().get_index_unchecked(index)) }
+    }
+
+    /// Returns the dynamic drawable according to its index.
+    ///
+    /// Only the data of this drawable is copied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    #[inline]
+    pub fn dynamic_drawable_index(&self, index: usize) -> Result<DynamicDrawable> {
+        self.dynamic_drawables().get_index(index)
+    }
+
+    /// Returns the render settings of a drawable according to its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    #[inline]
+    pub fn drawable_render_info(&self, index: usize) -> DrawableRenderInfo {
+        let flags = self.drawables.constant_flags[index];
+        DrawableRenderInfo {
+            blend_mode: flags.blend_mode(),
+            is_double_sided: flags.contains(ConstantFlags::IS_DOUBLE_SIDED),
+            is_inverted_mask: flags.contains(ConstantFlags::IS_INVERTED_MASK),
+            texture_index: self.drawables.texture_indices[index] as _,
+            mask_count: self.drawables.marks[index].len(),
         }
     }
 
+    /// Returns the static and dynamic data of a drawable according to its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    #[inline]
+    pub fn drawable_info(&self, index: usize) -> Result<DrawableInfo> {
+        Ok(DrawableInfo::new(
+            self.static_drawables().get_index(index),
+            self.dynamic_drawables().get_index(index)?,
+        ))
+    }
+
+    /// Returns an iterator over the indices of drawables whose dynamic flags contain `flags`.
     #[inline]
-    fn is_valid(&self) -> bool {
-        self.0 >= Self::ROOT
+    fn drawables_with_flags(
+        &self,
+        flags: DynamicFlags,
+    ) -> Result<impl Iterator<Item = usize> + '_> {
+        Ok(self
+            .drawable_dynamic_flags()?
+            .iter()
+            .enumerate()
+            .filter_map(move |(i, f)| if f.contains(flags) { Some(i) } else { None }))
     }
 
-    /// Checks if the parent index represents a root.
+    /// Returns `true` if any dynamic flag of drawables has been changed
+    /// by the last calling of [`update`](Self::update).
     #[inline]
-    pub fn is_root(&self) -> bool {
-        self.0 == Self::ROOT
+    pub fn any_drawable_changed(&self) -> bool {
+        let changed = DynamicFlags::all() - DynamicFlags::IS_VISIBLE;
+        self.drawables
+            .dynamic_flags
+            .iter()
+            .any(|f| f.intersects(changed))
     }
 
-    /// Returns the parent index.
+    /// Returns the count of drawables whose vertex positions have been changed
+    /// by the last calling of [`update`](Self::update).
+    #[inline]
+    pub fn changed_drawable_count(&self) -> Result<usize> {
+        Ok(self
+            .drawables_with_flags(DynamicFlags::VERTEX_POSITIONS_DID_CHANGE)?
+            .count())
+    }
+
+    /// Returns the indices of drawables whose render orders have been changed
+    /// by the last calling of [`update`](Self::update).
+    #[inline]
+    pub fn render_order_changed_drawables(&self) -> Result<Vec<usize>> {
+        Ok(self
+            .drawables_with_flags(DynamicFlags::RENDER_ORDER_DID_CHANGE)?
+            .collect())
+    }
+
+    /// Returns the indices of drawables sorted ascending by their render orders.
     ///
-    /// Returns [`None`] if the parent index represents a root.
+    /// Drawables with the same render order are sorted by their indices.
     #[inline]
-    pub fn parent(&self) -> Option<usize> {
-        if self.0 <= Self::ROOT {
-            None
-        } else {
-            Some(self.0 as _)
+    pub fn drawables_in_render_order(&self) -> Vec<usize> {
+        let mut indices = Vec::with_capacity(self.drawable_count());
+        self.drawables_in_render_order_into(&mut indices);
+        indices
+    }
+
+    /// Checks if the render orders of visible drawables are all distinct,
+    /// which should be called after calling [`update`](Self::update).
+    ///
+    /// If it returns `false`, the order of visible drawables with the same render order
+    /// is decided by their indices in [`drawables_in_render_order`](Self::drawables_in_render_order).
+    pub fn render_order_is_consistent(&self) -> Result<bool> {
+        let render_orders = self.drawable_render_orders();
+        let mut visible_orders = self
+            .drawables_with_flags(DynamicFlags::IS_VISIBLE)?
+            .map(|i| render_orders[i])
+            .collect::<Vec<_>>();
+        visible_orders.sort_unstable();
+
+        Ok(visible_orders.windows(2).all(|w| w[0] != w[1]))
+    }
+
+    /// Same as [`drawables_in_render_order`](Self::drawables_in_render_order),
+    /// but clears and fills `indices` instead of allocating a new vector.
+    pub fn drawables_in_render_order_into(&self, indices: &mut Vec<usize>) {
+        let render_orders = self.drawable_render_orders();
+        indices.clear();
+        indices.extend(0..self.drawable_count());
+        indices.sort_unstable_by_key(|i| (render_orders[*i], *i));
+    }
+
+    /// Returns an iterator over the triangles of all visible drawables in render order.
+    ///
+    /// Each item is the index of a drawable and the vertex positions of a triangle.
+    pub fn all_triangles(&self) -> impl Iterator<Item = (usize, [Vector2; 3])> + '_ {
+        let dynamic_flags = self.drawables.dynamic_flags;
+        self.drawables_in_render_order()
+            .into_iter()
+            .filter(move |i| dynamic_flags[*i].contains(DynamicFlags::IS_VISIBLE))
+            .flat_map(move |i| {
+                let positions = self.drawable_vertex_positions()[i];
+                self.drawable_triangles(i)
+                    .map(move |[a, b, c]| (i, [positions[a], positions[b], positions[c]]))
+            })
+    }
+
+    /// Returns the vertices of the triangles of all visible drawables in render order.
+    ///
+    /// Every three vertices form a triangle, and the color of a vertex is
+    /// the [`drawable_screen_tint`](Self::drawable_screen_tint) of its drawable.
+    pub fn triangle_soup(&self) -> Result<Vec<ColoredVertex>> {
+        let dynamic_flags = self.drawable_dynamic_flags()?;
+        let mut vertices = Vec::new();
+        for i in self.drawables_in_render_order() {
+            if !dynamic_flags[i].contains(DynamicFlags::IS_VISIBLE) {
+                continue;
+            }
+            let color = self.drawable_screen_tint(i)?;
+            let positions = self.drawable_vertex_positions()[i];
+            let uvs = self.drawable_vertex_uvs()[i];
+            vertices.extend(self.drawable_indices()[i].iter().map(|v| ColoredVertex {
+                position: positions[*v as usize],
+                uv: uvs[*v as usize],
+                color,
+            }));
         }
+
+        Ok(vertices)
     }
-}
 
-impl Default for PartParent {
+    /// Returns the axis-aligned bounding box `(min, max)` of the vertex positions of drawables
+    /// according to their indices.
+    ///
+    /// Returns [`None`] if there is no vertex in these drawables.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index is out of bound.
     #[inline]
-    fn default() -> Self {
-        Self(Self::ROOT)
+    pub fn bounding_box_of(&self, indices: &[usize]) -> Option<(Vector2, Vector2)> {
+        let positions = self.drawable_vertex_positions();
+        indices
+            .iter()
+            .filter_map(|i| get_bounds(positions[*i]))
+            .fold(None, |bounds, b| Some(union_bounds(bounds, b)))
     }
-}
 
-/// The model canvas.
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Canvas {
-    /// Canvas dimensions.
-    pub size_in_pixels: Vector2,
-    /// Origin of model on canvas.
-    pub origin_in_pixels: Vector2,
-    /// Aspect used for scaling pixels to units.
-    pub pixels_per_unit: f32,
-}
+    /// Returns the axis-aligned bounding box `(min, max)` of the vertex positions of a drawable
+    /// according to its index.
+    ///
+    /// Returns [`None`] if the drawable has no vertex.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bound.
+    #[inline]
+    pub fn drawable_bounds(&self, index: usize) -> Option<(Vector2, Vector2)> {
+        get_bounds(self.drawable_vertex_positions()[index])
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{
-        log::{set_logger, DefaultLogger},
-        read_haru_moc,
-    };
+    /// Writes the current meshes of drawables in the Wavefront OBJ format.
+    ///
+    /// Every drawable is an object named by its ID, and its vertex positions are written
+    /// as `v` lines with zero z values, its vertex uvs as `vt` lines and its triangles as `f` lines.
+    #[cfg(feature = "std")]
+    pub fn write_obj<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut offset = 1;
+        for (((id, positions), uvs), indices) in self
+            .drawable_ids()
+            .iter()
+            .zip(self.drawable_vertex_positions())
+            .zip(self.drawable_vertex_uvs())
+            .zip(self.drawable_indices())
+        {
+            writeln!(w, "o {}", id)?;
+            for position in positions.iter() {
+                writeln!(w, "v {} {} 0", position.x(), position.y())?;
+            }
+            for uv in uvs.iter() {
+                writeln!(w, "vt {} {}", uv.x(), uv.y())?;
+            }
+            for triangle in indices.chunks_exact(3) {
+                let (a, b, c) = (
+                    offset + triangle[0] as usize,
+                    offset + triangle[1] as usize,
+                    offset + triangle[2] as usize,
+                );
+                writeln!(w, "f {0}/{0} {1}/{1} {2}/{2}", a, b, c)?;
+            }
+            offset += positions.len();
+        }
 
-    #[test]
-    fn test_model() -> Result<()> {
-        set_logger(DefaultLogger);
+        Ok(())
+    }
+
+    /// Returns the axis-aligned bounding box `(min, max)` of the vertex positions of all visible drawables.
+    ///
+    /// Returns [`None`] if the dynamic flags are invalid or there is no visible vertex.
+    pub fn model_bounds(&self) -> Option<(Vector2, Vector2)> {
+        let positions = self.drawable_vertex_positions();
+        self.drawables_with_flags(DynamicFlags::IS_VISIBLE)
+            .ok()?
+            .filter_map(|i| get_bounds(positions[i]))
+            .fold(None, |bounds, b| Some(union_bounds(bounds, b)))
+    }
+
+    /// Returns the aspect ratio (width / height) of the bounding box of all visible drawables.
+    ///
+    /// Returns [`None`] if the dynamic flags are invalid, there is no visible vertex
+    /// or the height of the bounding box is zero.
+    pub fn geometry_aspect_ratio(&self) -> Option<f32> {
+        let (min, max) = self.model_bounds()?;
+        let height = max.y() - min.y();
+        if height > 0. {
+            Some((max.x() - min.x()) / height)
+        } else {
+            None
+        }
+    }
+}
+
+/// A builder which creates [`Model`] with initial parameter values and part opacities.
+///
+/// IDs are resolved in [`build`](Self::build), so unknown IDs return an error
+/// instead of panicking like [`Model::set_parameter_value`].
+#[derive(Clone, Debug)]
+pub struct ModelBuilder {
+    moc: Moc,
+    parameters: HashMap<String, f32>,
+    part_opacities: HashMap<String, f32>,
+}
+
+impl ModelBuilder {
+    /// Creates [`ModelBuilder`] from a moc.
+    #[inline]
+    pub fn from_moc(moc: Moc) -> Self {
+        Self {
+            moc,
+            parameters: HashMap::new(),
+            part_opacities: HashMap::new(),
+        }
+    }
+
+    /// Sets the initial value of a parameter according to its ID.
+    #[inline]
+    pub fn parameter<T: Into<String>>(mut self, id: T, value: f32) -> Self {
+        self.parameters.insert(id.into(), value);
+        self
+    }
+
+    /// Sets the initial opacity of a part according to its ID.
+    #[inline]
+    pub fn part_opacity<T: Into<String>>(mut self, id: T, opacity: f32) -> Self {
+        self.part_opacities.insert(id.into(), opacity);
+        self
+    }
+
+    /// Creates [`Model`], sets the initial values and then calls [`update`](Model::update).
+    ///
+    /// Returns an [`UnknownIds`](Error::UnknownIds) error with all parameter IDs and part IDs
+    /// which don't exist in sorted order if there is any.
+    pub fn build<'a>(self) -> Result<Model<'a>> {
+        let mut model = Model::new(self.moc)?;
+        let mut unknown_ids = Vec::new();
+        for (id, value) in self.parameters {
+            match model.parameter_index(&id) {
+                Some(index) => model.parameter_values_mut()[index] = value,
+                None => unknown_ids.push(id),
+            }
+        }
+        for (id, opacity) in self.part_opacities {
+            match model.part_index(&id) {
+                Some(index) => model.part_opacities_mut()[index] = opacity,
+                None => unknown_ids.push(id),
+            }
+        }
+
+        if unknown_ids.is_empty() {
+            model.update();
+            Ok(model)
+        } else {
+            unknown_ids.sort_unstable();
+            Err(Error::UnknownIds(unknown_ids))
+        }
+    }
+}
+
+/// Two dimension vector.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug)]
+pub struct Vector2(cubism_core_sys::csmVector2);
+
+impl Vector2 {
+    /// Creates [`Vector2`]
+    #[inline]
+    pub fn new(x: f32, y: f32) -> Self {
+        Self(cubism_core_sys::csmVector2 { X: x, Y: y })
+    }
+
+    /// Returns the x value of a vector.
+    #[inline]
+    pub fn x(&self) -> f32 {
+        self.0.X
+    }
+
+    /// Returns the y value of a vector.
+    #[inline]
+    pub fn y(&self) -> f32 {
+        self.0.Y
+    }
+
+    /// Returns the x value and y value of a vector.
+    #[inline]
+    pub fn x_y(&self) -> (f32, f32) {
+        (self.0.X, self.0.Y)
+    }
+
+    /// Returns the dot product of two vectors.
+    #[inline]
+    pub fn dot(&self, other: Self) -> f32 {
+        self.0.X * other.0.X + self.0.Y * other.0.Y
+    }
+
+    /// Returns the length of a vector.
+    #[inline]
+    pub fn length(&self) -> f32 {
+        hypot(self.0.X, self.0.Y)
+    }
+
+    /// Returns the linear interpolation `self * (1 - t) + other * t` of two vectors.
+    #[inline]
+    pub fn lerp(&self, other: Self, t: f32) -> Self {
+        *self + (other - *self) * t
+    }
+
+    /// Returns the raw bits of the x value and y value of a vector.
+    ///
+    /// It can be used as a key for hashing or deduplicating vectors by their exact bit patterns,
+    /// so `0.0` and `-0.0` are different and NaNs with the same bits are equal.
+    #[inline]
+    pub fn to_bits(&self) -> (u32, u32) {
+        (self.0.X.to_bits(), self.0.Y.to_bits())
+    }
+
+    /// Creates [`Vector2`] from the raw bits of the x value and y value.
+    #[inline]
+    pub fn from_bits(bits: (u32, u32)) -> Self {
+        Self::new(f32::from_bits(bits.0), f32::from_bits(bits.1))
+    }
+}
+
+impl Add for Vector2 {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.0.X + rhs.0.X, self.0.Y + rhs.0.Y)
+    }
+}
+
+impl AddAssign for Vector2 {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Vector2 {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.0.X - rhs.0.X, self.0.Y - rhs.0.Y)
+    }
+}
+
+impl SubAssign for Vector2 {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<f32> for Vector2 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: f32) -> Self {
+        Self::new(self.0.X * rhs, self.0.Y * rhs)
+    }
+}
+
+impl MulAssign<f32> for Vector2 {
+    #[inline]
+    fn mul_assign(&mut self, rhs: f32) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div<f32> for Vector2 {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, rhs: f32) -> Self {
+        Self::new(self.0.X / rhs, self.0.Y / rhs)
+    }
+}
+
+impl DivAssign<f32> for Vector2 {
+    #[inline]
+    fn div_assign(&mut self, rhs: f32) {
+        *self = *self / rhs;
+    }
+}
+
+impl Neg for Vector2 {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::new(-self.0.X, -self.0.Y)
+    }
+}
+
+impl Default for Vector2 {
+    #[inline]
+    fn default() -> Self {
+        Self(cubism_core_sys::csmVector2 { X: 0., Y: 0. })
+    }
+}
+
+impl PartialEq for Vector2 {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0.X == other.0.X && self.0.Y == other.0.Y
+    }
+}
+
+impl From<cubism_core_sys::csmVector2> for Vector2 {
+    #[inline]
+    fn from(vector: cubism_core_sys::csmVector2) -> Self {
+        Self(vector)
+    }
+}
+
+impl From<Vector2> for cubism_core_sys::csmVector2 {
+    #[inline]
+    fn from(vector: Vector2) -> Self {
+        vector.0
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<Vector2> for glam::Vec2 {
+    #[inline]
+    fn from(vector: Vector2) -> Self {
+        Self::new(vector.x(), vector.y())
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<glam::Vec2> for Vector2 {
+    #[inline]
+    fn from(vector: glam::Vec2) -> Self {
+        Self::new(vector.x, vector.y)
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<Vector2> for mint::Point2<f32> {
+    #[inline]
+    fn from(vector: Vector2) -> Self {
+        Self {
+            x: vector.x(),
+            y: vector.y(),
+        }
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<mint::Point2<f32>> for Vector2 {
+    #[inline]
+    fn from(point: mint::Point2<f32>) -> Self {
+        Self::new(point.x, point.y)
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<Vector2> for mint::Vector2<f32> {
+    #[inline]
+    fn from(vector: Vector2) -> Self {
+        Self {
+            x: vector.x(),
+            y: vector.y(),
+        }
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<mint::Vector2<f32>> for Vector2 {
+    #[inline]
+    fn from(vector: mint::Vector2<f32>) -> Self {
+        Self::new(vector.x, vector.y)
+    }
+}
+
+/// [`Vector2`] is serialized as a two-element sequence `[x, y]`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Vector2 {
+    #[inline]
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        self.x_y().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Vector2 {
+    #[inline]
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        let (x, y) = serde::Deserialize::deserialize(deserializer)?;
+        Ok(Self::new(x, y))
+    }
+}
+
+/// The type of a parameter.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParameterType {
+    /// Normal parameter.
+    Normal,
+    /// Parameter for blend shape.
+    BlendShape,
+}
+
+impl ParameterType {
+    #[inline]
+    fn from_raw(ty: cubism_core_sys::csmParameterType) -> Option<Self> {
+        if ty == cubism_core_sys::csmParameterType_Normal as _ {
+            Some(Self::Normal)
+        } else if ty == cubism_core_sys::csmParameterType_BlendShape as _ {
+            Some(Self::BlendShape)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the [`ParameterType`] is [`Normal`](ParameterType::Normal).
+    #[inline]
+    pub fn is_normal(&self) -> bool {
+        matches!(self, Self::Normal)
+    }
+
+    /// Returns `true` if the [`ParameterType`] is [`BlendShape`](ParameterType::BlendShape).
+    #[inline]
+    pub fn is_blend_shape(&self) -> bool {
+        matches!(self, Self::BlendShape)
+    }
+}
+
+impl Default for ParameterType {
+    #[inline]
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// Four dimension vector.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug)]
+pub struct Vector4(cubism_core_sys::csmVector4);
+
+impl Vector4 {
+    /// Creates [`Vector4`]
+    #[inline]
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self(cubism_core_sys::csmVector4 {
+            X: x,
+            Y: y,
+            Z: z,
+            W: w,
+        })
+    }
+
+    /// Returns the x value of a vector.
+    #[inline]
+    pub fn x(&self) -> f32 {
+        self.0.X
+    }
+
+    /// Returns the y value of a vector.
+    #[inline]
+    pub fn y(&self) -> f32 {
+        self.0.Y
+    }
+
+    /// Returns the z value of a vector.
+    #[inline]
+    pub fn z(&self) -> f32 {
+        self.0.Z
+    }
+
+    /// Returns the w value of a vector.
+    #[inline]
+    pub fn w(&self) -> f32 {
+        self.0.W
+    }
+
+    /// Returns the x value, y value, z value and w value of a vector.
+    #[inline]
+    pub fn x_y_z_w(&self) -> (f32, f32, f32, f32) {
+        (self.0.X, self.0.Y, self.0.Z, self.0.W)
+    }
+}
+
+impl Default for Vector4 {
+    #[inline]
+    fn default() -> Self {
+        Self::new(0., 0., 0., 0.)
+    }
+}
+
+impl PartialEq for Vector4 {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0.X == other.0.X
+            && self.0.Y == other.0.Y
+            && self.0.Z == other.0.Z
+            && self.0.W == other.0.W
+    }
+}
+
+impl From<cubism_core_sys::csmVector4> for Vector4 {
+    #[inline]
+    fn from(vector: cubism_core_sys::csmVector4) -> Self {
+        Self(vector)
+    }
+}
+
+impl From<Vector4> for cubism_core_sys::csmVector4 {
+    #[inline]
+    fn from(vector: Vector4) -> Self {
+        vector.0
+    }
+}
+
+/// The parent index of a part.
+///
+/// A part has a parent, or it is a root.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartParent(i32);
+
+impl PartParent {
+    /// The parent index of a part when the part is a root.
+    pub const ROOT: i32 = -1;
+
+    /// Creates [`PartParent`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parent index is less than [`ROOT`](Self::ROOT).
+    #[inline]
+    pub fn new(parent_index: Option<usize>) -> Self {
+        match parent_index {
+            Some(i) => {
+                assert!(i <= i32::MAX as _);
+                Self(i as _)
+            }
+            None => Self(Self::ROOT),
+        }
+    }
+
+    #[inline]
+    fn is_valid(&self) -> bool {
+        self.0 >= Self::ROOT
+    }
+
+    /// Checks if the parent index represents a root.
+    #[inline]
+    pub fn is_root(&self) -> bool {
+        self.0 == Self::ROOT
+    }
+
+    /// Returns the parent index.
+    ///
+    /// Returns [`None`] if the parent index represents a root.
+    #[inline]
+    pub fn parent(&self) -> Option<usize> {
+        if self.0 <= Self::ROOT {
+            None
+        } else {
+            Some(self.0 as _)
+        }
+    }
+}
+
+impl Default for PartParent {
+    #[inline]
+    fn default() -> Self {
+        Self(Self::ROOT)
+    }
+}
+
+/// The saved parameter values and part opacities of a model.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelState {
+    /// The values of parameters.
+    pub parameter_values: Vec<f32>,
+    /// The opacities of parts.
+    pub part_opacities: Vec<f32>,
+}
+
+/// The owned static data of a model.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModelSnapshot {
+    /// Static parameters.
+    pub parameters: Vec<StaticParameter>,
+    /// Static parts.
+    pub parts: Vec<StaticPart>,
+    /// Static drawables.
+    pub drawables: Vec<StaticDrawable>,
+    /// The model canvas.
+    pub canvas: Canvas,
+}
+
+/// The model canvas.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Canvas {
+    /// Canvas dimensions.
+    pub size_in_pixels: Vector2,
+    /// Origin of model on canvas.
+    pub origin_in_pixels: Vector2,
+    /// Aspect used for scaling pixels to units.
+    pub pixels_per_unit: f32,
+}
+
+impl Canvas {
+    /// Converts a position in model units to pixels.
+    ///
+    /// `pixel = model * pixels_per_unit + origin_in_pixels`
+    #[inline]
+    pub fn model_to_pixel(&self, position: Vector2) -> Vector2 {
+        position * self.pixels_per_unit + self.origin_in_pixels
+    }
+
+    /// Converts a position in pixels to model units.
+    ///
+    /// `model = (pixel - origin_in_pixels) / pixels_per_unit`
+    #[inline]
+    pub fn pixel_to_model(&self, position: Vector2) -> Vector2 {
+        (position - self.origin_in_pixels) / self.pixels_per_unit
+    }
+
+    /// Returns the aspect ratio (width / height) of the canvas.
+    #[inline]
+    pub fn aspect_ratio(&self) -> f32 {
+        self.size_in_pixels.x() / self.size_in_pixels.y()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::log::{set_logger, DefaultLogger};
+    use crate::read_haru_moc;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_model() -> Result<()> {
+        #[cfg(feature = "std")]
+        set_logger(DefaultLogger);
+        let moc = read_haru_moc()?;
+        let _model = Model::new(moc)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameter_value_clamped_to() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        let min = model.parameter_min_values()[0];
+        let max = model.parameter_max_values()[0];
+        let mid = (min + max) / 2.;
+        let _ = model.set_parameter_value_index(0, max);
+        assert_eq!(model.parameter_value_clamped_to(0, min, mid), mid);
+        assert_eq!(model.parameter_value_clamped_to(0, min - 1., max + 1.), max);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_changed_drawable_count() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        // nothing has been changed since the last update.
+        model.update();
+        assert_eq!(model.changed_drawable_count()?, 0);
+
+        let prev = model
+            .drawable_vertex_positions()
+            .iter()
+            .map(|p| p.to_vec())
+            .collect::<Vec<_>>();
+        let angle_x = model
+            .parameter_ids()
+            .iter()
+            .position(|id| *id == "ParamAngleX")
+            .unwrap();
+        model.set_parameter_value_index(angle_x, 30.);
+        model.update();
+        let moved = model
+            .drawable_vertex_positions()
+            .iter()
+            .zip(&prev)
+            .filter(|(current, prev)| current[..] != prev[..])
+            .count();
+        assert!(moved > 0);
+        assert_eq!(model.changed_drawable_count()?, moved);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_all_part_opacities() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.set_all_part_opacities(0.5);
+        assert!(model.part_opacities().iter().all(|o| *o == 0.5));
+        model.set_all_part_opacities(2.);
+        assert!(model.part_opacities().iter().all(|o| *o == 1.));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_has_out_of_range_parameters() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        assert!(!model.has_out_of_range_parameters());
+        let max = model.parameter_max_values()[0];
+        let _ = model.set_parameter_value_index(0, max + 1.);
+        assert!(model.has_out_of_range_parameters());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blend_from() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        let mut a = Model::new_from_model(&model)?;
+        let mut b = Model::new_from_model(&model)?;
+        let _ = a.set_parameter_value_index(0, a.parameter_min_values()[0]);
+        let _ = b.set_parameter_value_index(0, b.parameter_max_values()[0]);
+        a.set_all_part_opacities(0.);
+        b.set_all_part_opacities(1.);
+
+        model.blend_from(&a, &b, 0.)?;
+        assert_eq!(model.parameter_values(), a.parameter_values());
+        assert_eq!(model.part_opacities(), a.part_opacities());
+        model.blend_from(&a, &b, 1.)?;
+        assert_eq!(model.parameter_values(), b.parameter_values());
+        assert_eq!(model.part_opacities(), b.part_opacities());
+
+        // a model from another moc of the same file is compared by IDs.
+        let other = Model::new(read_haru_moc()?)?;
+        assert_ne!(other.moc().as_moc_ptr(), model.moc().as_moc_ptr());
+        model.blend_from(&other, &b, 1.)?;
+        assert_eq!(model.parameter_values(), b.parameter_values());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bounding_box_of() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        assert!(model.bounding_box_of(&[]).is_none());
+        let all = (0..model.drawable_count()).collect::<Vec<_>>();
+        let (all_min, all_max) = model.bounding_box_of(&all).unwrap();
+        let (min, max) = model.bounding_box_of(&all[..2]).unwrap();
+        assert!(min.x() >= all_min.x() && min.y() >= all_min.y());
+        assert!(max.x() <= all_max.x() && max.y() <= all_max.y());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drawable_flags_raw() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        for i in 0..model.drawable_count() {
+            assert_eq!(
+                model.drawable_constant_flags_raw(i),
+                model.drawable_constant_flags()[i].bits()
+            );
+            assert_eq!(
+                model.drawable_dynamic_flags_raw(i),
+                model.drawable_dynamic_flags()?[i].bits()
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_triangles() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        let visible_count: usize = model
+            .drawable_dynamic_flags()?
+            .iter()
+            .zip(model.drawable_indices())
+            .filter(|(f, _)| f.contains(DynamicFlags::IS_VISIBLE))
+            .map(|(_, i)| i.len() / 3)
+            .sum();
+        assert_eq!(model.all_triangles().count(), visible_count);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drawable_screen_tint() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        let white = Vector4::new(1., 1., 1., 1.);
+        for i in 0..model.drawable_count() {
+            if model.drawable_multiply_colors()[i] == white {
+                let opacity = model.drawable_opacities()?[i];
+                assert_eq!(model.drawable_screen_tint(i)?.w(), opacity);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameter_ranges() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let ranges = model.parameter_ranges();
+        assert_eq!(ranges.len(), model.parameter_count());
+        for (i, (min, max, default)) in ranges.into_iter().enumerate() {
+            assert_eq!(min, model.parameter_min_values()[i]);
+            assert_eq!(max, model.parameter_max_values()[i]);
+            assert_eq!(default, model.parameter_default_values()[i]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mask_drawables() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let mask_drawables = model.mask_drawables();
+        for i in 0..model.drawable_count() {
+            assert_eq!(model.is_mask_drawable(i), mask_drawables.contains(&i));
+        }
+        for (i, masks) in model.drawable_masks().iter().enumerate() {
+            for m in masks.iter().map(|m| *m as usize).filter(|m| *m != i) {
+                assert!(mask_drawables.contains(&m));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scale_part_opacities() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        let opacities = model.part_opacities().to_vec();
+        model.scale_part_opacities(0.5);
+        for (o, scaled) in opacities.iter().zip(model.part_opacities()) {
+            assert_eq!((o * 0.5).clamp(0., 1.), *scaled);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sizes() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let (moc_size, model_size) = model.sizes();
+        assert!(moc_size > 0);
+        assert!(model_size > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_in() -> Result<()> {
+        let moc = read_haru_moc()?;
+        let expected = Model::new(moc.clone())?;
+        let (_, size) = expected.sizes();
+        let model = Model::new_in(moc.clone(), AlignedBytes::new_zeroed(size, ALIGN_OF_MODEL))?;
+        assert_eq!(model.parameter_ids(), expected.parameter_ids());
+        assert_eq!(model.parameter_values(), expected.parameter_values());
+        assert_eq!(model.part_opacities(), expected.part_opacities());
+        assert_eq!(model.drawable_ids(), expected.drawable_ids());
+
+        let model = Model::new_in(moc.clone(), model.into_buffer())?;
+        assert_eq!(model.parameter_values(), expected.parameter_values());
+
+        assert!(Model::new_in(moc, AlignedBytes::new_zeroed(size - 1, ALIGN_OF_MODEL)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_parameters_to_min() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.set_parameters_to_min();
+        assert_eq!(model.parameter_values(), model.parameter_min_values());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_parameters_to_max() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.set_parameters_to_max();
+        assert_eq!(model.parameter_values(), model.parameter_max_values());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_order_changed_drawables() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        // nothing has been changed since the last update.
+        model.update();
+        assert_eq!(model.render_order_changed_drawables()?, Vec::<usize>::new());
+
+        let prev = model.drawable_render_orders().to_vec();
+        model.set_parameters_to_max();
+        model.update();
+        let expected = model
+            .drawable_render_orders()
+            .iter()
+            .zip(&prev)
+            .enumerate()
+            .filter(|(_, (current, prev))| current != prev)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        assert_eq!(model.render_order_changed_drawables()?, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_rows() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        assert_eq!(model.part_rows().count(), model.part_count());
+        let (id, opacity, parent) = model.part_rows().next().unwrap();
+        assert_eq!(id, model.part_ids()[0]);
+        assert_eq!(opacity, model.part_opacities()[0]);
+        assert_eq!(parent, model.part_parent()[0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_geometry_aspect_ratio() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        assert!(model.geometry_aspect_ratio().unwrap() > 0.);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drawables_in_texture_range() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let texture_count = *model.drawable_texture_indices().iter().max().unwrap() as usize + 1;
+        assert_eq!(
+            model.drawables_in_texture_range(0, texture_count),
+            (0..model.drawable_count()).collect::<Vec<_>>()
+        );
+        assert!(model.drawables_in_texture_range(1, 1).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameter_delta_from() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        let state = model.save_state();
+        assert!(model.parameter_delta_from(&state)?.is_empty());
+
+        let max = model.parameter_max_values()[0];
+        let min = model.parameter_min_values()[1];
+        let defaults = model.parameter_default_values().to_vec();
+        // the first two parameters of Haru aren't at their bounds by default.
+        assert_ne!(max, defaults[0]);
+        assert_ne!(min, defaults[1]);
+        let _ = model.set_parameter_value_index(0, max);
+        let _ = model.set_parameter_value_index(1, min);
+        assert_eq!(
+            model.parameter_delta_from(&state)?,
+            vec![(0, max - defaults[0]), (1, min - defaults[1])]
+        );
+
+        let changed = model.save_state();
+        model.reset_parameter_values();
+        assert_eq!(
+            model.parameter_delta_from(&changed)?,
+            vec![(0, defaults[0] - max), (1, defaults[1] - min)]
+        );
+
+        model.restore_state(&state)?;
+        assert!(model.parameter_delta_from(&state)?.is_empty());
+
+        let mut truncated = state.clone();
+        let _ = truncated.parameter_values.pop();
+        assert_eq!(
+            model.parameter_delta_from(&truncated),
+            Err(Error::SliceLengthNotEqual(
+                model.parameter_count() - 1,
+                model.parameter_count()
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hidden_parts() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        for i in model.hidden_parts() {
+            assert!(model.part_opacities()[i] < F32_EPSILON);
+        }
+        let _ = model.set_part_opacity_index(0, 0.);
+        assert!(model.hidden_parts().contains(&0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignore_masks() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        for i in 0..model.drawable_count() {
+            assert_eq!(model.effective_masks(i), model.drawable_masks()[i]);
+        }
+        model.set_ignore_masks(true);
+        assert!(model.ignore_masks());
+        for i in 0..model.drawable_count() {
+            assert!(model.effective_masks(i).is_empty());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_part_depth() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let max_depth = model.max_part_depth();
+        assert!(max_depth <= model.part_count());
+        assert!((0..model.part_count()).all(|i| model.part_depth(i) <= max_depth));
+        assert!((0..model.part_count()).any(|i| model.part_depth(i) == max_depth));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drawable_blend_colors() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        assert_eq!(
+            model.drawable_multiply_colors().len(),
+            model.drawable_count()
+        );
+        assert_eq!(model.drawable_screen_colors().len(), model.drawable_count());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameter_types() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        assert_eq!(model.parameter_types().len(), model.parameter_count());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_orphan_drawables() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        // all drawables of Haru are grouped under parts.
+        assert!(model.orphan_drawables().is_empty());
+        for parent in model.drawable_parent_parts() {
+            assert!(parent.parent().unwrap() < model.part_count());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameter_ids_sorted() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let ids = model.parameter_ids_sorted();
+        let mut expected = model.parameter_ids().to_vec();
+        expected.sort_unstable();
+        assert_eq!(ids, expected);
+        assert!(ids.contains(&"ParamAngleX"));
+        // the ID maps of different models don't share the iteration order.
+        let other = Model::new(read_haru_moc()?)?;
+        assert_eq!(other.parameter_ids_sorted(), ids);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_vertex_deltas_from() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        let prev = model.drawable_vertex_positions()[0].to_vec();
+        assert!(model
+            .vertex_deltas_from(0, &prev)
+            .iter()
+            .all(|d| *d == Vector2::default()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.set_parameters_to_max();
+        model.set_all_part_opacities(0.);
+        model.reset_parameter_values();
+        model.reset_part_opacities();
+        assert_eq!(model.parameter_values(), model.parameter_default_values());
+        assert!(model.part_opacities().iter().all(|o| *o == 1.));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_flattened_key_values() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let (values, offsets) = model.flattened_key_values();
+        assert_eq!(offsets.len(), model.parameter_count());
+        for (i, key_values) in model.parameter_key_values().iter().enumerate() {
+            let end = offsets.get(i + 1).copied().unwrap_or(values.len());
+            assert_eq!(&values[offsets[i]..end], *key_values);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_parameter_value_clamped() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        let id = model.parameter_ids()[0].to_string();
+        let max = model.parameter_max_values()[0];
+        let min = model.parameter_min_values()[0];
+        assert_eq!(model.set_parameter_value_clamped(&id, max + 1.), max);
+        assert_eq!(model.parameter_values()[0], max);
+        assert_eq!(model.set_parameter_value_index_clamped(0, min - 1.), min);
+        assert_eq!(model.parameter_values()[0], min);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameter_info() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let id = model.parameter_ids()[0];
+        let info = model.parameter_info(id).unwrap();
+        assert_eq!(info.index, 0);
+        assert_eq!(info.id, id);
+        assert!(model.parameter_info("unknown parameter ID").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_triangle_soup() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        let vertices = model.triangle_soup()?;
+        assert_eq!(vertices.len() % 3, 0);
+        assert_eq!(vertices.len() / 3, model.all_triangles().count());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drawable_bounds() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        let (model_min, model_max) = model.model_bounds().expect("no visible vertex");
+        for (i, flags) in model.drawable_dynamic_flags()?.iter().enumerate() {
+            if let Some((min, max)) = model.drawable_bounds(i) {
+                assert!(min.x() <= max.x() && min.y() <= max.y());
+                if flags.contains(DynamicFlags::IS_VISIBLE) {
+                    assert!(model_min.x() <= min.x() && model_min.y() <= min.y());
+                    assert!(model_max.x() >= max.x() && model_max.y() >= max.y());
+                }
+            } else {
+                assert!(model.drawable_vertex_positions()[i].is_empty());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameters_where_keys() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let indices = model.parameters_where_keys(|k| k.len() > 3);
+        let key_values = model.parameter_key_values();
+        assert_eq!(
+            indices.len(),
+            key_values.iter().filter(|k| k.len() > 3).count()
+        );
+        assert!(indices.iter().all(|i| key_values[*i].len() > 3));
+        assert_eq!(
+            model.parameters_where_keys(|_| true).len(),
+            model.parameter_count()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_vertex_displacement() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        let prev = model
+            .drawable_vertex_positions()
+            .iter()
+            .map(|p| Box::from(*p))
+            .collect::<Vec<Box<[Vector2]>>>();
+        assert_eq!(model.max_vertex_displacement(&prev), 0.);
+
+        model.set_parameter_value("ParamAngleX", 30.);
+        model.update();
+        let mut expected = 0f32;
+        for (current, prev) in model.drawable_vertex_positions().iter().zip(&prev) {
+            for (current, prev) in current.iter().zip(prev.iter()) {
+                let (dx, dy) = (current.x() - prev.x(), current.y() - prev.y());
+                expected = expected.max(libm::sqrtf(dx * dx + dy * dy));
+            }
+        }
+        assert!(expected > 0.);
+        assert!((model.max_vertex_displacement(&prev) - expected).abs() < F32_EPSILON);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drawables_in_render_order() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        let indices = model.drawables_in_render_order();
+        assert_eq!(indices.len(), model.drawable_count());
+        let render_orders = model.drawable_render_orders();
+        for w in indices.windows(2) {
+            assert!((render_orders[w[0]], w[0]) < (render_orders[w[1]], w[1]));
+        }
+        let mut buffer = vec![usize::MAX; 3];
+        model.drawables_in_render_order_into(&mut buffer);
+        assert_eq!(buffer, indices);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_branching_part_count() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let count = model.branching_part_count();
+        assert!(count <= model.part_count());
+        assert_eq!(count > 0, model.part_parent().iter().any(|p| !p.is_root()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_order_is_consistent() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        // the render orders of Haru are a permutation of the drawable indices.
+        let mut render_orders = model.drawable_render_orders().to_vec();
+        render_orders.sort_unstable();
+        assert_eq!(
+            render_orders,
+            (0..model.drawable_count() as i32).collect::<Vec<_>>()
+        );
+        assert!(model
+            .drawable_dynamic_flags()?
+            .iter()
+            .any(|f| f.contains(DynamicFlags::IS_VISIBLE)));
+        assert!(model.render_order_is_consistent()?);
+        model.set_parameters_to_max();
+        model.update();
+        assert!(model.render_order_is_consistent()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drawable_part_id_map() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let map = model.drawable_part_id_map();
+        assert_eq!(map.len(), model.drawable_count());
+        let id = model.drawable_ids()[0];
+        let expected = model.drawable_parent_parts()[0]
+            .parent()
+            .map(|p| model.part_ids()[p]);
+        assert_eq!(map[id], expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_state_from() -> Result<()> {
         let moc = read_haru_moc()?;
-        let _model = Model::new(moc)?;
+        let mut model = Model::new(moc.clone())?;
+        let mut other = Model::new(moc)?;
+        other.set_parameters_to_max();
+        other.set_all_part_opacities(0.5);
+        model.copy_state_from(&other)?;
+        assert_eq!(model.parameter_values(), other.parameter_values());
+        assert_eq!(model.part_opacities(), other.part_opacities());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drawable_triangles() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        for i in 0..model.drawable_count() {
+            let vertex_count = model.drawable_vertex_positions()[i].len();
+            let triangles = model.drawable_triangles(i).collect::<Vec<_>>();
+            assert_eq!(triangles.len() * 3, model.drawable_indices()[i].len());
+            assert!(triangles.iter().flatten().all(|v| *v < vertex_count));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_vector2_ops() {
+        let a = Vector2::new(1., 2.);
+        let b = Vector2::new(3., -4.);
+        assert_eq!(a + b, Vector2::new(4., -2.));
+        assert_eq!(a - b, Vector2::new(-2., 6.));
+        assert_eq!(a * 2., Vector2::new(2., 4.));
+        assert_eq!(b / 2., Vector2::new(1.5, -2.));
+        assert_eq!(-a, Vector2::new(-1., -2.));
+        let mut c = a;
+        c += b;
+        c -= a;
+        c *= 2.;
+        c /= 4.;
+        assert_eq!(c, Vector2::new(1.5, -2.));
+        assert_eq!(a.dot(b), -5.);
+        assert_eq!(b.length(), 5.);
+        assert_eq!(a.lerp(b, 0.5), Vector2::new(2., -1.));
+    }
+
+    #[test]
+    fn test_dynamic_drawable() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        let index = model.drawable_count() - 1;
+        let id = model.drawable_ids()[index];
+        let drawable = model.dynamic_drawable(id).unwrap()?;
+        assert_eq!(drawable.index, index);
+        assert_eq!(drawable.id, id);
+        assert_eq!(
+            drawable.dynamic_flags,
+            model.drawable_dynamic_flags()?[index]
+        );
+        assert_eq!(drawable.draw_order, model.drawable_draw_orders()[index]);
+        assert_eq!(drawable.render_order, model.drawable_render_orders()[index]);
+        assert_eq!(drawable.opacity, model.drawable_opacities()?[index]);
+        assert_eq!(
+            drawable.vertex_positions,
+            model.drawable_vertex_positions()[index]
+        );
+        assert_eq!(
+            drawable.multiply_color,
+            model.drawable_multiply_colors()[index]
+        );
+        assert_eq!(drawable.screen_color, model.drawable_screen_colors()[index]);
+        assert!(model.dynamic_drawable("unknown drawable ID").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_masks() {
+        let masks = [0u32, 2, 1];
+        unsafe {
+            assert_eq!(get_masks(masks.as_ptr(), masks.len(), 3), Some(&masks[..]));
+            assert!(get_masks(masks.as_ptr(), masks.len(), 2).is_none());
+        }
+    }
+
+    #[test]
+    fn test_effective_part_opacities() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.set_all_part_opacities(0.5);
+        let opacities = model.effective_part_opacities();
+        assert_eq!(opacities.len(), model.part_count());
+        for (i, o) in opacities.iter().enumerate() {
+            assert!((o - model.effective_part_opacity(i)).abs() < F32_EPSILON);
+            let expected = 0.5f32.powi(model.part_depth(i) as i32 + 1);
+            assert!((o - expected).abs() < F32_EPSILON);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_effective_opacities_of_cyclic_parents() {
+        // 4 and 5 are cyclic, 2 is processed before its ancestor 3 while 6 is after it,
+        // and the parent of 7 is out of bound.
+        let parents = [
+            None,
+            Some(0),
+            Some(3),
+            Some(4),
+            Some(5),
+            Some(4),
+            Some(3),
+            Some(99),
+        ]
+        .iter()
+        .map(|p| PartParent::new(*p))
+        .collect::<Vec<_>>();
+        let opacities = [0.5, 0.8, 0.9, 0.7, 0.6, 0.4, 0.3, 0.2];
+        let effective = effective_opacities(&parents, &opacities);
+        assert_eq!(effective, vec![0.5, 0.4, 0.9, 0.7, 0.6, 0.4, 0.3, 0.2]);
+        for (i, o) in effective.iter().enumerate() {
+            assert_eq!(*o, effective_opacity(&parents, &opacities, i));
+        }
+    }
+
+    #[test]
+    fn test_update_and_changed() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        let changed = model.update_and_changed();
+        assert_eq!(
+            changed.render_orders,
+            model.render_order_changed_drawables()?
+        );
+        assert_eq!(
+            changed.vertex_positions.len(),
+            model.changed_drawable_count()?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameter_range() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let id = model.parameter_ids()[0];
+        let (min, max) = model.parameter_range(0);
+        assert_eq!(model.parameter_range_by_id(id), Some((min, max)));
+        assert!(model
+            .parameter_range_by_id("unknown parameter ID")
+            .is_none());
+        let info = model.parameter_current_info(0);
+        assert_eq!((info.min_value, info.max_value), (min, max));
+        assert_eq!(info.default_value, model.parameter_default_values()[0]);
+        assert_eq!(info.value, model.parameter_values()[0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_set_values() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        let parameter_count = model.parameter_count();
+        let part_count = model.part_count();
+        assert_eq!(
+            model.try_set_parameter_values(vec![0.; parameter_count + 1]),
+            Err(Error::SliceLengthNotEqual(
+                parameter_count + 1,
+                parameter_count
+            ))
+        );
+        assert_eq!(
+            model.try_set_part_opacities([]),
+            Err(Error::SliceLengthNotEqual(0, part_count))
+        );
+        let min_values = model.parameter_min_values().to_vec();
+        model.try_set_parameter_values(&min_values)?;
+        assert_eq!(model.parameter_values(), min_values);
+        model.try_set_part_opacities(vec![0.5; part_count])?;
+        assert!(model.part_opacities().iter().all(|o| *o == 0.5));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drawable_vertex_counts() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let counts = model.drawable_vertex_counts();
+        assert_eq!(counts.len(), model.drawable_count());
+        for (i, count) in counts.iter().enumerate() {
+            assert_eq!(*count, model.drawable_vertex_count(i));
+            assert_eq!(*count, model.drawable_vertex_uvs()[i].len());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot() -> Result<()> {
+        let snapshot = {
+            let model = Model::new(read_haru_moc()?)?;
+            model.snapshot()
+        };
+        let model = Model::new(read_haru_moc()?)?;
+        assert_eq!(snapshot.parameters.len(), model.parameter_count());
+        assert_eq!(snapshot.parts.len(), model.part_count());
+        assert_eq!(snapshot.drawables.len(), model.drawable_count());
+        assert_eq!(snapshot, model.snapshot());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_global_opacity() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        assert_eq!(model.global_opacity(), 1.);
+        model.set_global_opacity(0.5);
+        assert_eq!(model.global_opacity(), 0.5);
+        for (i, o) in model.drawable_opacities()?.iter().enumerate() {
+            assert!((model.effective_drawable_opacity(i)? - o * 0.5).abs() < F32_EPSILON);
+        }
+        model.set_global_opacity(2.);
+        for i in 0..model.drawable_count() {
+            assert!(model.effective_drawable_opacity(i)? <= 1.);
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_update_batch() -> Result<()> {
+        let moc = read_haru_moc()?;
+        let mut models = (0..4)
+            .map(|_| Model::new(moc.clone()))
+            .collect::<Result<Vec<_>>>()?;
+        Model::update_batch(&mut models);
+        let mut model = Model::new(moc)?;
+        model.update();
+        for m in &models {
+            assert_eq!(
+                m.drawable_vertex_positions(),
+                model.drawable_vertex_positions()
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_any_drawable_changed() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        let changed = model.update_and_changed();
+        if !changed.is_empty() {
+            assert!(model.any_drawable_changed());
+        }
+        let visible_only = model
+            .drawable_dynamic_flags()?
+            .iter()
+            .all(|f| (*f - DynamicFlags::IS_VISIBLE).is_empty());
+        assert_eq!(model.any_drawable_changed(), !visible_only);
+
+        Ok(())
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "the moc of the model has been replaced")]
+    fn test_check_moc() {
+        let mut model = Model::new(read_haru_moc().unwrap()).unwrap();
+        model.check_moc();
+        model.moc = read_haru_moc().unwrap();
+        let _ = model.as_model_ptr();
+    }
+
+    #[test]
+    fn test_canvas_conversions() -> Result<()> {
+        let canvas = Canvas {
+            size_in_pixels: Vector2::new(200., 100.),
+            origin_in_pixels: Vector2::new(100., 50.),
+            pixels_per_unit: 100.,
+        };
+        assert_eq!(
+            canvas.model_to_pixel(Vector2::new(0.5, -0.25)),
+            Vector2::new(150., 25.)
+        );
+        assert_eq!(
+            canvas.pixel_to_model(Vector2::new(150., 25.)),
+            Vector2::new(0.5, -0.25)
+        );
+        assert_eq!(canvas.aspect_ratio(), 2.);
+
+        let model = Model::new(read_haru_moc()?)?;
+        let canvas = model.read_canvas_info();
+        let position = Vector2::new(0.1, 0.2);
+        let converted = canvas.pixel_to_model(canvas.model_to_pixel(position));
+        assert!((converted - position).x().abs() < F32_EPSILON);
+        assert!((converted - position).y().abs() < F32_EPSILON);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameter_value_and_part_opacity() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        assert_eq!(model.parameter_value("ParamAngleX"), Some(0.));
+        let index = model.parameter_count() - 1;
+        let (min, max) = model.parameter_range(index);
+        let value = (min + max) / 2.;
+        model.set_parameter_value_index(index, value);
+        assert_eq!(
+            model.parameter_value(model.parameter_ids()[index]),
+            Some(value)
+        );
+        assert!(model.parameter_value("unknown parameter ID").is_none());
+        let index = model.part_count() - 1;
+        model.set_part_opacity_index(index, 0.5);
+        assert_eq!(model.part_opacity(model.part_ids()[index]), Some(0.5));
+        assert!(model.part_opacity("unknown part ID").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_masked_drawables() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let masked = model.masked_drawable_indices();
+        assert!(!masked.is_empty());
+        assert!(model.masked_drawables().eq(masked.iter().copied()));
+        for (i, masks) in model.drawable_masks().iter().enumerate() {
+            assert_eq!(masked.contains(&i), !masks.is_empty());
+        }
+        for i in model.inverted_masked_drawables() {
+            assert!(masked.contains(&i));
+            assert!(model.is_inverted_masked(i));
+        }
+        for i in 0..model.drawable_count() {
+            assert_eq!(
+                model.is_inverted_masked(i),
+                model.inverted_masked_drawables().any(|j| j == i)
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_vector2_bits() {
+        let v = Vector2::new(1.5, -2.0);
+        assert_eq!(Vector2::from_bits(v.to_bits()), v);
+        assert_ne!(
+            Vector2::new(0.0, 0.0).to_bits(),
+            Vector2::new(-0.0, 0.0).to_bits()
+        );
+        assert_eq!(
+            Vector2::new(f32::NAN, 0.0).to_bits(),
+            Vector2::new(f32::NAN, 0.0).to_bits()
+        );
+        let set: alloc::collections::BTreeSet<_> =
+            [v, Vector2::new(1.5, -2.0), Vector2::new(0.0, 1.0)]
+                .iter()
+                .map(Vector2::to_bits)
+                .collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_blend_parameter_values() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        let max_values = model.parameter_max_values().to_vec();
+        model.blend_parameter_values(&max_values, 0.0)?;
+        assert_eq!(model.parameter_values(), model.parameter_default_values());
+        model.blend_parameter_values(&max_values, 1.0)?;
+        assert_eq!(model.parameter_values(), model.parameter_max_values());
+
+        model.reset_parameter_values();
+        let targets = vec![f32::MAX; model.parameter_count()];
+        model.blend_parameter_values(&targets, 0.5)?;
+        assert_eq!(model.parameter_values(), model.parameter_max_values());
+        assert_eq!(
+            model.blend_parameter_values(&targets[1..], 0.5),
+            Err(Error::SliceLengthNotEqual(
+                model.parameter_count() - 1,
+                model.parameter_count()
+            ))
+        );
+
+        model.reset_parameter_values();
+        let id = model.parameter_ids()[0].to_string();
+        let (min, max) = (
+            model.parameter_min_values()[0],
+            model.parameter_max_values()[0],
+        );
+        let default = model.parameter_default_values()[0];
+        let value = model.blend_parameter_value(&id, max, 0.5);
+        assert!((value - (default + (max - default) * 0.5)).abs() < F32_EPSILON);
+        assert_eq!(model.parameter_value(&id), Some(value));
+        assert_eq!(model.blend_parameter_value(&id, min - 100.0, 1.0), min);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameter_is_default() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        assert!((0..model.parameter_count()).all(|i| model.parameter_is_default(i)));
+        assert!(model.changed_parameter_indices().is_empty());
+
+        let index = (0..model.parameter_count())
+            .find(|i| model.parameter_max_values()[*i] > model.parameter_default_values()[*i])
+            .unwrap();
+        let default = model.parameter_default_values()[index];
+        model.set_parameter_value_index(index, default + F32_EPSILON / 2.0);
+        assert!(model.parameter_is_default(index));
+        model.set_parameter_value_index_clamped(index, f32::MAX);
+        assert!(!model.parameter_is_default(index));
+        assert_eq!(model.changed_parameter_indices(), vec![index]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_model_builder() -> Result<()> {
+        let moc = read_haru_moc()?;
+        let reference = Model::new(moc.clone())?;
+        let parameter_id = reference.parameter_ids()[0].to_string();
+        let value = reference.parameter_max_values()[0];
+        let part_id = reference.part_ids()[0].to_string();
+
+        let model = ModelBuilder::from_moc(moc.clone())
+            .parameter(parameter_id.as_str(), value)
+            .part_opacity(part_id.as_str(), 0.5)
+            .build()?;
+        assert_eq!(model.parameter_value(&parameter_id), Some(value));
+        assert_eq!(model.part_opacity(&part_id), Some(0.5));
+
+        assert_eq!(
+            ModelBuilder::from_moc(moc.clone())
+                .parameter("NoSuchParameter", 0.0)
+                .build()
+                .err(),
+            Some(Error::UnknownIds(vec!["NoSuchParameter".to_string()]))
+        );
+        assert_eq!(
+            ModelBuilder::from_moc(moc)
+                .part_opacity("NoSuchPartB", 0.0)
+                .parameter(parameter_id.as_str(), value)
+                .parameter("NoSuchParameter", 0.0)
+                .part_opacity("NoSuchPartA", 0.0)
+                .build()
+                .err(),
+            Some(Error::UnknownIds(vec![
+                "NoSuchParameter".to_string(),
+                "NoSuchPartA".to_string(),
+                "NoSuchPartB".to_string(),
+            ]))
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_write_obj() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        let mut obj = Vec::new();
+        model.write_obj(&mut obj).unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+
+        let count = |prefix: &str| obj.lines().filter(|l| l.starts_with(prefix)).count();
+        assert_eq!(count("o "), model.drawable_count());
+        let vertex_count: usize = model.drawable_vertex_counts().iter().sum();
+        assert_eq!(count("v "), vertex_count);
+        assert_eq!(count("vt "), vertex_count);
+        let index_count: usize = model.drawable_indices().iter().map(|i| i.len()).sum();
+        assert_eq!(count("f "), index_count / 3);
+        for line in obj.lines().filter(|l| l.starts_with("f ")) {
+            for vertex in line[2..].split(' ') {
+                let index: usize = vertex.split('/').next().unwrap().parse().unwrap();
+                assert!((1..=vertex_count).contains(&index));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameter_indices_by_type() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let (normal, blend_shape) = model.parameter_indices_by_type();
+        // Haru is a `moc3` 3.0 model without blend shapes.
+        assert_eq!(normal, (0..model.parameter_count()).collect::<Vec<_>>());
+        assert!(blend_shape.is_empty());
+
+        let types = [
+            ParameterType::Normal,
+            ParameterType::BlendShape,
+            ParameterType::Normal,
+        ];
+        assert_eq!(indices_by_type(Some(&types), 3), (vec![0, 2], vec![1]));
+        // the fallback for a Cubism Core lib without parameter types.
+        assert_eq!(indices_by_type(None, 3), (vec![0, 1, 2], vec![]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset_dynamic_flags() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update_only();
+        assert!(model.any_drawable_changed());
+        model.reset_dynamic_flags();
+        assert!(!model.any_drawable_changed());
+        assert!(model.drawable_dynamic_flags()?.iter().all(|f| f.is_valid()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_model_size() -> Result<()> {
+        let moc = read_haru_moc()?;
+        let model = Model::new(moc.clone())?;
+        assert_eq!(model.model_size(), model.sizes().1);
+        assert_eq!(model.model_size(), get_model_size(moc.as_moc_ptr())?);
+        assert!(model.total_footprint() > model.model_size());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_tree() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        let tree = model.part_tree();
+        assert!(!tree.roots().is_empty());
+        for i in tree.roots() {
+            assert!(model.part_parent()[*i].is_root());
+        }
+        for i in 0..model.part_count() {
+            assert_eq!(tree.children(i), model.part_children(i).as_slice());
+        }
+        let reachable: usize = tree
+            .roots()
+            .iter()
+            .map(|i| 1 + tree.descendants(*i).len())
+            .sum();
+        assert_eq!(reachable, model.part_count());
+
+        let cyclic = PartTree::new(&[
+            PartParent::new(Some(1)),
+            PartParent::new(Some(0)),
+            PartParent::new(None),
+            PartParent::new(Some(9)),
+        ]);
+        assert_eq!(cyclic.roots(), &[2, 3]);
+        assert_eq!(cyclic.descendants(0), vec![1]);
+        assert_eq!(cyclic.descendants(1), vec![0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_render() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        let renderable = model.renderable_drawable_indices()?;
+        assert!(!renderable.is_empty());
+        let flags = model.drawable_dynamic_flags()?;
+        let opacities = model.drawable_opacities()?;
+        for i in 0..model.drawable_count() {
+            assert_eq!(
+                renderable.contains(&i),
+                flags[i].contains(DynamicFlags::IS_VISIBLE) && opacities[i] > F32_EPSILON
+            );
+            assert_eq!(model.should_render(i)?, renderable.contains(&i));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_static_parameter_and_part_refs() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        assert_eq!(model.static_parameter_refs().len(), model.parameter_count());
+        for (r, p) in model.static_parameter_refs().zip(model.static_parameters()) {
+            assert_eq!(r.index, p.index);
+            assert_eq!(r.id, p.id);
+            assert_eq!(r.ty, p.ty);
+            assert_eq!(r.min_value, p.min_value);
+            assert_eq!(r.max_value, p.max_value);
+            assert_eq!(r.default_value, p.default_value);
+            assert_eq!(r.key_values, &p.key_values[..]);
+        }
+
+        assert_eq!(model.static_part_refs().len(), model.part_count());
+        for (r, p) in model.static_part_refs().zip(model.static_parts()) {
+            assert_eq!(r.index, p.index);
+            assert_eq!(r.id, p.id);
+            assert_eq!(r.parent, p.parent);
+        }
+        let id = model.part_ids()[0];
+        assert_eq!(model.static_part_refs().get(id).id, id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drawable_opacities_valid() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        assert!(model.drawables.opacities_valid);
+        model.update();
+        assert!(model.drawables.opacities_valid);
+        assert_eq!(model.drawable_opacities()?, model.drawables.opacities);
+
+        // the readers of opacities don't skip the validation.
+        model.drawables.opacities_valid = false;
+        let error = Error::GetDataError("drawable opacities");
+        assert_eq!(model.effective_drawable_opacity(0).unwrap_err(), error);
+        assert_eq!(model.should_render(0).unwrap_err(), error);
+        assert_eq!(model.renderable_drawable_indices().unwrap_err(), error);
+        assert_eq!(model.drawable_screen_tint(0).unwrap_err(), error);
+        model.update_only();
+        assert!(model.drawables.opacities_valid);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_parameter_values_from_map() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        let id = model.parameter_ids()[0].to_string();
+        let value = model.parameter_max_values()[0];
+        model.set_parameter_values_from_map(vec![(id.as_str(), value)])?;
+        assert_eq!(model.parameter_value(&id), Some(value));
+
+        let mut map = HashMap::new();
+        map.insert("NoSuchParameter", 1.0);
+        map.insert(id.as_str(), model.parameter_min_values()[0]);
+        assert_eq!(
+            model.set_parameter_values_from_map(map),
+            Err(Error::UnknownIds(vec!["NoSuchParameter".to_string()]))
+        );
+        assert_eq!(
+            model.parameter_value(&id),
+            Some(model.parameter_min_values()[0])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drawable_vertex_positions_mut() -> Result<()> {
+        let mut model = Model::new(read_haru_moc()?)?;
+        model.update();
+        let original = model.drawable_vertex_positions()[0].to_vec();
+        let offset = Vector2::new(1.0, 2.0);
+        for p in model.drawable_vertex_positions_mut(0).unwrap() {
+            *p += offset;
+        }
+        for (p, o) in model.drawable_vertex_positions()[0].iter().zip(&original) {
+            assert_eq!(*p, *o + offset);
+        }
+        assert!(model
+            .drawable_vertex_positions_mut(model.drawable_count())
+            .is_none());
+
+        for i in 0..model.drawable_count() {
+            for p in model.drawable_vertex_positions_mut(i).unwrap() {
+                *p += offset;
+            }
+        }
+        let written = model
+            .drawable_vertex_positions()
+            .iter()
+            .map(|p| p.to_vec())
+            .collect::<Vec<_>>();
+        model.set_parameter_value("ParamAngleX", 30.);
+        model.update();
+        let flags = model.drawable_dynamic_flags()?;
+        let mut overwritten = 0;
+        for (i, positions) in model.drawable_vertex_positions().iter().enumerate() {
+            if flags[i].contains(DynamicFlags::VERTEX_POSITIONS_DID_CHANGE) {
+                assert_ne!(positions[..], written[i][..]);
+                overwritten += 1;
+            }
+        }
+        assert!(overwritten > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drawable_uv_bounds() -> Result<()> {
+        let model = Model::new(read_haru_moc()?)?;
+        assert_eq!(model.drawable_uv_bounds_all().len(), model.drawable_count());
+        for (i, uvs) in model.drawable_vertex_uvs().iter().enumerate() {
+            match model.drawable_uv_bounds(i) {
+                Some((min, max)) => {
+                    assert!(uvs.iter().all(|uv| {
+                        (min.x()..=max.x()).contains(&uv.x())
+                            && (min.y()..=max.y()).contains(&uv.y())
+                    }));
+                    assert!(uvs.iter().any(|uv| uv.x() == min.x()));
+                    assert!(uvs.iter().any(|uv| uv.y() == max.y()));
+                }
+                None => assert!(uvs.is_empty()),
+            }
+        }
 
         Ok(())
     }
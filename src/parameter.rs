@@ -1,14 +1,25 @@
 //! Parameters of the Cubism model.
 
-use crate::{impl_iter, Model, ModelData};
+use crate::{
+    impl_iter,
+    model::{Model, ParameterType},
+    ModelData,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 
 /// A static parameter.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StaticParameter {
     /// The index of a parameter.
     pub index: usize,
     /// The ID of a parameter.
     pub id: String,
+    /// The type of a parameter.
+    pub ty: ParameterType,
     /// The minimal value of a parameter.
     pub min_value: f32,
     /// The maximal value of a parameter.
@@ -58,6 +69,7 @@ impl<'a> ModelData for StaticParameters<'a> {
         StaticParameter {
             index,
             id: self.model.parameter_ids().get_unchecked(index).to_string(),
+            ty: *self.model.parameter_types().get_unchecked(index),
             min_value: *self.model.parameter_min_values().get_unchecked(index),
             max_value: *self.model.parameter_max_values().get_unchecked(index),
             default_value: *self.model.parameter_default_values().get_unchecked(index),
@@ -71,3 +83,89 @@ impl<'a> ModelData for StaticParameters<'a> {
 }
 
 impl_iter!(StaticParameters<'a>, StaticParameter, Vec<StaticParameter>);
+
+/// A static parameter which borrows its data from [`Model`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StaticParameterRef<'a> {
+    /// The index of a parameter.
+    pub index: usize,
+    /// The ID of a parameter.
+    pub id: &'a str,
+    /// The type of a parameter.
+    pub ty: ParameterType,
+    /// The minimal value of a parameter.
+    pub min_value: f32,
+    /// The maximal value of a parameter.
+    pub max_value: f32,
+    /// The default value of a parameter.
+    pub default_value: f32,
+    /// The key values of a parameter.
+    pub key_values: &'a [f32],
+}
+
+/// Static parameters which borrow their data from [`Model`].
+#[derive(Debug)]
+pub struct StaticParameterRefs<'a> {
+    model: &'a Model<'a>,
+    /// The initialization value is 0.
+    start: usize,
+    /// The initialization value is the count of parameters.
+    end: usize,
+}
+
+impl<'a> StaticParameterRefs<'a> {
+    #[inline]
+    pub(crate) fn new(model: &'a Model<'a>) -> Self {
+        Self {
+            model,
+            start: 0,
+            end: model.parameter_count(),
+        }
+    }
+}
+
+impl<'a> ModelData for StaticParameterRefs<'a> {
+    type Data = StaticParameterRef<'a>;
+
+    #[inline]
+    fn count(&self) -> usize {
+        self.model.parameter_count()
+    }
+
+    #[inline]
+    fn index<T: AsRef<str>>(&self, id: T) -> Option<usize> {
+        self.model.parameter_index(id)
+    }
+
+    #[inline]
+    unsafe fn get_index_unchecked(&self, index: usize) -> Self::Data {
+        StaticParameterRef {
+            index,
+            id: self.model.parameter_ids().get_unchecked(index),
+            ty: *self.model.parameter_types().get_unchecked(index),
+            min_value: *self.model.parameter_min_values().get_unchecked(index),
+            max_value: *self.model.parameter_max_values().get_unchecked(index),
+            default_value: *self.model.parameter_default_values().get_unchecked(index),
+            key_values: self.model.parameter_key_values().get_unchecked(index),
+        }
+    }
+}
+
+impl_iter!(
+    StaticParameterRefs<'a>,
+    StaticParameterRef<'a>,
+    Vec<StaticParameterRef<'a>>
+);
+
+/// The range and the current value of a parameter.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ParameterInfo {
+    /// The minimal value of a parameter.
+    pub min_value: f32,
+    /// The maximal value of a parameter.
+    pub max_value: f32,
+    /// The default value of a parameter.
+    pub default_value: f32,
+    /// The current value of a parameter.
+    pub value: f32,
+}
@@ -5,9 +5,16 @@ use crate::{
     model::{Model, PartParent},
     ModelData,
 };
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 
 /// A static part.
 #[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StaticPart {
     /// The index of a part.
     pub index: usize,
@@ -62,3 +69,133 @@ impl<'a> ModelData for StaticParts<'a> {
 }
 
 impl_iter!(StaticParts<'a>, StaticPart, Vec<StaticPart>);
+
+/// A static part which borrows its data from [`Model`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct StaticPartRef<'a> {
+    /// The index of a part.
+    pub index: usize,
+    /// The ID of a part.
+    pub id: &'a str,
+    /// The parent index of a part.
+    pub parent: PartParent,
+}
+
+/// Static parts which borrow their data from [`Model`].
+#[derive(Debug)]
+pub struct StaticPartRefs<'a> {
+    model: &'a Model<'a>,
+    /// The initialization value is 0.
+    start: usize,
+    /// The initialization value is the count of parts.
+    end: usize,
+}
+
+impl<'a> StaticPartRefs<'a> {
+    #[inline]
+    pub(crate) fn new(model: &'a Model<'a>) -> Self {
+        Self {
+            model,
+            start: 0,
+            end: model.part_count(),
+        }
+    }
+}
+
+impl<'a> ModelData for StaticPartRefs<'a> {
+    type Data = StaticPartRef<'a>;
+
+    #[inline]
+    fn count(&self) -> usize {
+        self.model.part_count()
+    }
+
+    #[inline]
+    fn index<T: AsRef<str>>(&self, id: T) -> Option<usize> {
+        self.model.part_index(id)
+    }
+
+    #[inline]
+    unsafe fn get_index_unchecked(&self, index: usize) -> Self::Data {
+        StaticPartRef {
+            index,
+            id: self.model.part_ids().get_unchecked(index),
+            parent: *self.model.part_parent().get_unchecked(index),
+        }
+    }
+}
+
+impl_iter!(
+    StaticPartRefs<'a>,
+    StaticPartRef<'a>,
+    Vec<StaticPartRef<'a>>
+);
+
+/// The children of parts, which are precomputed from the parent indices of parts.
+///
+/// Parts with out of bound parent indices are treated as roots.
+/// Parts in malformed cyclic parents aren't reachable from the roots,
+/// and they are visited at most once by [`descendants`](Self::descendants).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PartTree {
+    roots: Vec<usize>,
+    children: Box<[Vec<usize>]>,
+}
+
+impl PartTree {
+    pub(crate) fn new(parents: &[PartParent]) -> Self {
+        let mut roots = Vec::new();
+        let mut children = vec![Vec::new(); parents.len()].into_boxed_slice();
+        for (index, parent) in parents.iter().enumerate() {
+            match parent.parent() {
+                Some(p) if p < parents.len() => {
+                    if p != index {
+                        children[p].push(index);
+                    }
+                }
+                _ => roots.push(index),
+            }
+        }
+
+        Self { roots, children }
+    }
+
+    /// Returns the indices of root parts.
+    #[inline]
+    pub fn roots(&self) -> &[usize] {
+        &self.roots
+    }
+
+    /// Returns the indices of the children of a part according to its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    #[inline]
+    pub fn children(&self, index: usize) -> &[usize] {
+        &self.children[index]
+    }
+
+    /// Returns the indices of all descendants of a part in depth-first order,
+    /// not including the part itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bound.
+    pub fn descendants(&self, index: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.children.len()];
+        visited[index] = true;
+        let mut descendants = Vec::new();
+        let mut stack: Vec<usize> = self.children[index].iter().rev().copied().collect();
+        while let Some(i) = stack.pop() {
+            if visited[i] {
+                continue;
+            }
+            visited[i] = true;
+            descendants.push(i);
+            stack.extend(self.children[i].iter().rev());
+        }
+
+        descendants
+    }
+}
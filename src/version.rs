@@ -7,6 +7,8 @@ pub struct CubismVersion {
 
 impl CubismVersion {
     /// Returns the version of the Cubism Core lib.
+    ///
+    /// With the `dlopen` feature, the version is 0 if the Cubism Core lib isn't loaded.
     #[inline]
     pub fn version() -> Self {
         unsafe {
@@ -35,9 +37,9 @@ impl CubismVersion {
     }
 }
 
-impl std::fmt::Display for CubismVersion {
+impl core::fmt::Display for CubismVersion {
     #[inline]
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "{}.{}.{} ({})",
@@ -50,16 +52,24 @@ impl std::fmt::Display for CubismVersion {
 }
 
 /// `moc3` file format version.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+///
+/// It's compared and hashed by the raw version number, so an unknown newer version
+/// is greater than all known versions, and [`VersionUnknown`](MocVersion::VersionUnknown)
+/// with the raw number of a known version equals that version.
+#[derive(Clone, Copy, Debug)]
 pub enum MocVersion {
     /// `moc3` file version 3.0.00 - 3.2.07
     Version30,
     /// `moc3` file version 3.3.00 - 3.3.03
     Version33,
-    /// `moc3` file version 4.0.00
+    /// `moc3` file version 4.0.00 - 4.1.05
     Version40,
-    /// unknown `moc3` file version
-    VersionUnknown,
+    /// `moc3` file version 4.2.00 - 4.2.04
+    Version42,
+    /// `moc3` file version 5.0.00 -
+    Version50,
+    /// unknown `moc3` file version, with the raw version number
+    VersionUnknown(cubism_core_sys::csmMocVersion),
 }
 
 impl MocVersion {
@@ -70,11 +80,31 @@ impl MocVersion {
             1 => MocVersion::Version30,
             2 => MocVersion::Version33,
             3 => MocVersion::Version40,
-            _ => MocVersion::VersionUnknown,
+            4 => MocVersion::Version42,
+            5 => MocVersion::Version50,
+            _ => MocVersion::VersionUnknown(version),
+        }
+    }
+
+    /// Returns the raw version number.
+    #[inline]
+    pub fn raw(&self) -> cubism_core_sys::csmMocVersion {
+        match self {
+            MocVersion::Version30 => cubism_core_sys::csmMocVersion_30,
+            MocVersion::Version33 => cubism_core_sys::csmMocVersion_33,
+            MocVersion::Version40 => cubism_core_sys::csmMocVersion_40,
+            MocVersion::Version42 => cubism_core_sys::csmMocVersion_42,
+            MocVersion::Version50 => cubism_core_sys::csmMocVersion_50,
+            MocVersion::VersionUnknown(version) => *version,
         }
     }
 
     /// Returns the latest version which `moc3` file is supported.
+    ///
+    /// It also tells the features of the Cubism Core lib, for example,
+    /// Cubism Core 5.0 returns [`Version50`](MocVersion::Version50).
+    ///
+    /// With the `dlopen` feature, it's `VersionUnknown(0)` if the Cubism Core lib isn't loaded.
     #[inline]
     pub fn latest_version() -> Self {
         unsafe { cubism_core_sys::csmGetLatestMocVersion().into() }
@@ -98,10 +128,66 @@ impl MocVersion {
         matches!(self, Self::Version40)
     }
 
+    /// Returns `true` if the [`MocVersion`] is [`Version42`](MocVersion::Version42).
+    #[inline]
+    pub fn is_version42(self) -> bool {
+        matches!(self, Self::Version42)
+    }
+
+    /// Returns `true` if the [`MocVersion`] is [`Version50`](MocVersion::Version50).
+    #[inline]
+    pub fn is_version50(self) -> bool {
+        matches!(self, Self::Version50)
+    }
+
     /// Returns `true` if the [`MocVersion`] is [`VersionUnknown`](MocVersion::VersionUnknown).
     #[inline]
     pub fn is_version_unknown(self) -> bool {
-        matches!(self, Self::VersionUnknown)
+        matches!(self, Self::VersionUnknown(_))
+    }
+}
+
+impl PartialEq for MocVersion {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.raw() == other.raw()
+    }
+}
+
+impl Eq for MocVersion {}
+
+impl PartialOrd for MocVersion {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MocVersion {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.raw().cmp(&other.raw())
+    }
+}
+
+impl core::hash::Hash for MocVersion {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.raw().hash(state);
+    }
+}
+
+impl PartialEq<cubism_core_sys::csmMocVersion> for MocVersion {
+    #[inline]
+    fn eq(&self, other: &cubism_core_sys::csmMocVersion) -> bool {
+        self.raw() == *other
+    }
+}
+
+impl PartialOrd<cubism_core_sys::csmMocVersion> for MocVersion {
+    #[inline]
+    fn partial_cmp(&self, other: &cubism_core_sys::csmMocVersion) -> Option<core::cmp::Ordering> {
+        Some(self.raw().cmp(other))
     }
 }
 
@@ -115,6 +201,7 @@ impl From<cubism_core_sys::csmMocVersion> for MocVersion {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::{vec, vec::Vec};
 
     #[test]
     fn test_cubism_version() {
@@ -130,6 +217,38 @@ mod tests {
     #[test]
     fn test_moc_version() {
         let latest_version = MocVersion::latest_version();
-        assert!(latest_version.is_version40());
+        assert!(latest_version >= MocVersion::Version50);
+        assert!(MocVersion::new(4).is_version42());
+        assert!(MocVersion::new(5).is_version50());
+        assert_eq!(MocVersion::new(0), MocVersion::VersionUnknown(0));
+        assert!(MocVersion::new(100).is_version_unknown());
+    }
+
+    #[test]
+    fn test_moc_version_ord() {
+        assert!(MocVersion::Version30 < MocVersion::Version33);
+        assert!(MocVersion::Version33 < MocVersion::Version40);
+        assert!(MocVersion::Version40 < MocVersion::Version42);
+        assert!(MocVersion::Version42 < MocVersion::Version50);
+        assert!(MocVersion::new(6) > MocVersion::Version50);
+        assert!(MocVersion::new(100) > MocVersion::new(6));
+        assert!(MocVersion::new(0) < MocVersion::Version30);
+        assert_eq!(MocVersion::VersionUnknown(3), MocVersion::Version40);
+        assert_eq!(MocVersion::Version40, 3);
+        assert!(MocVersion::Version40 < 4);
+
+        let mut versions = [
+            MocVersion::new(4),
+            MocVersion::new(6),
+            MocVersion::Version50,
+            MocVersion::Version40,
+            MocVersion::Version30,
+            MocVersion::Version33,
+        ];
+        versions.sort();
+        assert_eq!(
+            versions.iter().map(MocVersion::raw).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5, 6]
+        );
     }
 }